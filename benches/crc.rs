@@ -0,0 +1,24 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use nvmbuilder::layout::crc_catalogue;
+use nvmbuilder::output::checksum::{calculate_crc, calculate_crc_bitwise};
+use std::hint::black_box;
+
+fn crc_engines(c: &mut Criterion) {
+    let data: Vec<u8> = (0..1 << 20).map(|i: u32| (i * 31 + 7) as u8).collect();
+    let mut group = c.benchmark_group("crc_1MiB");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    for name in ["CRC-16/XMODEM", "CRC-32/ISO-HDLC", "CRC-64/XZ"] {
+        let crc = &crc_catalogue::find(name).unwrap().crc;
+        group.bench_function(format!("{name} bitwise"), |b| {
+            b.iter(|| calculate_crc_bitwise(black_box(&data), crc))
+        });
+        group.bench_function(format!("{name} table"), |b| {
+            b.iter(|| calculate_crc(black_box(&data), crc))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, crc_engines);
+criterion_main!(benches);
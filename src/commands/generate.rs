@@ -3,14 +3,15 @@ use crate::commands::stats::BlockStat;
 use crate::error::NvmError;
 use crate::layout;
 use crate::layout::args::BlockNames;
+use crate::layout::build_info::BuildInfo;
 use crate::layout::errors::LayoutError;
-use crate::layout::settings::Endianness;
 use crate::variant::DataSheet;
 use crate::writer::write_output;
 
 pub fn build_block_single(
     input: &BlockNames,
     data_sheet: Option<&DataSheet>,
+    build_info: &BuildInfo,
     args: &Args,
 ) -> Result<BlockStat, NvmError> {
     let result = (|| {
@@ -22,14 +23,15 @@ pub fn build_block_single(
             .ok_or(LayoutError::BlockNotFound(input.name.clone()))?;
 
         let (bytestream, padding_bytes) =
-            block.build_bytestream(data_sheet, &layout.settings, args.layout.strict)?;
+            block.build_bytestream(data_sheet, &layout, args.layout.strict, build_info)?;
 
+        let settings = block.effective_settings(&layout);
         let data_range = crate::output::bytestream_to_datarange(
             bytestream,
             &block.header,
-            &layout.settings,
-            layout.settings.byte_swap,
-            layout.settings.pad_to_end,
+            &settings,
+            settings.byte_swap,
+            settings.pad_to_end,
             padding_bytes,
         )?;
 
@@ -41,20 +43,8 @@ pub fn build_block_single(
 
         write_output(&args.output, &input.name, &hex_string)?;
 
-        let crc_value = match layout.settings.endianness {
-            Endianness::Big => u32::from_be_bytes([
-                data_range.crc_bytestream[0],
-                data_range.crc_bytestream[1],
-                data_range.crc_bytestream[2],
-                data_range.crc_bytestream[3],
-            ]),
-            Endianness::Little => u32::from_le_bytes([
-                data_range.crc_bytestream[0],
-                data_range.crc_bytestream[1],
-                data_range.crc_bytestream[2],
-                data_range.crc_bytestream[3],
-            ]),
-        };
+        let (crc_value, digest) =
+            BlockStat::integrity_values(&data_range.crc_bytestream, &settings);
 
         Ok(BlockStat {
             name: input.name.clone(),
@@ -62,6 +52,8 @@ pub fn build_block_single(
             allocated_size: data_range.allocated_size,
             used_size: data_range.used_size,
             crc_value,
+            crc_width: settings.crc.width,
+            digest,
         })
     })();
 
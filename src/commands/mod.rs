@@ -4,6 +4,7 @@ pub mod stats;
 use crate::args::Args;
 use crate::error::NvmError;
 use crate::layout;
+use crate::layout::build_info::BuildInfo;
 use crate::layout::errors::LayoutError;
 use crate::output;
 use crate::output::errors::OutputError;
@@ -18,12 +19,13 @@ pub fn build_separate_blocks(
     data_sheet: Option<&DataSheet>,
 ) -> Result<BuildStats, NvmError> {
     let start_time = Instant::now();
+    let build_info = BuildInfo::from_args(args)?;
 
     let block_stats: Result<Vec<BlockStat>, NvmError> = args
         .layout
         .blocks
         .par_iter()
-        .map(|input| generate::build_block_single(input, data_sheet, args))
+        .map(|input| generate::build_block_single(input, data_sheet, &build_info, args))
         .collect();
 
     let block_stats = block_stats?;
@@ -42,6 +44,7 @@ pub fn build_single_file(
     data_sheet: Option<&DataSheet>,
 ) -> Result<BuildStats, NvmError> {
     let start_time = Instant::now();
+    let build_info = BuildInfo::from_args(args)?;
 
     let mut ranges = Vec::new();
     let mut block_ranges: Vec<(String, u32, u32)> = Vec::new();
@@ -58,31 +61,20 @@ pub fn build_single_file(
                     .ok_or(LayoutError::BlockNotFound(input.name.clone()))?;
 
                 let (bytestream, padding_bytes) =
-                    block.build_bytestream(data_sheet, &layout.settings, args.layout.strict)?;
+                    block.build_bytestream(data_sheet, &layout, args.layout.strict, &build_info)?;
 
+                let settings = block.effective_settings(&layout);
                 let dr = output::bytestream_to_datarange(
                     bytestream,
                     &block.header,
-                    &layout.settings,
-                    layout.settings.byte_swap,
-                    layout.settings.pad_to_end,
+                    &settings,
+                    settings.byte_swap,
+                    settings.pad_to_end,
                     padding_bytes,
                 )?;
 
-                let mut crc_bytes = [
-                    dr.crc_bytestream[0],
-                    dr.crc_bytestream[1],
-                    dr.crc_bytestream[2],
-                    dr.crc_bytestream[3],
-                ];
-                if layout.settings.byte_swap {
-                    crc_bytes.swap(0, 1);
-                    crc_bytes.swap(2, 3);
-                }
-                let crc_value = match layout.settings.endianness {
-                    layout::settings::Endianness::Big => u32::from_be_bytes(crc_bytes),
-                    layout::settings::Endianness::Little => u32::from_le_bytes(crc_bytes),
-                };
+                let (crc_value, digest) =
+                    BlockStat::integrity_values(&dr.crc_bytestream, &settings);
 
                 let stat = BlockStat {
                     name: input.name.clone(),
@@ -90,12 +82,14 @@ pub fn build_single_file(
                     allocated_size: dr.allocated_size,
                     used_size: dr.used_size,
                     crc_value,
+                    crc_width: settings.crc.width,
+                    digest,
                 };
 
                 let start = block
                     .header
                     .start_address
-                    .checked_add(layout.settings.virtual_offset)
+                    .checked_add(settings.virtual_offset)
                     .ok_or(LayoutError::InvalidBlockArgument(
                         "start_address + virtual_offset overflow".into(),
                     ))?;
@@ -1,3 +1,4 @@
+use crate::layout::settings::{CrcWidth, Endianness, Settings};
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -6,7 +7,31 @@ pub struct BlockStat {
     pub start_address: u32,
     pub allocated_size: u32,
     pub used_size: u32,
-    pub crc_value: u32,
+    pub crc_value: u64,
+    /// Register width of 'crc_value'.
+    pub crc_width: CrcWidth,
+    /// Block digest when one replaces the CRC; 'crc_value' is then 0.
+    pub digest: Option<Vec<u8>>,
+}
+
+impl BlockStat {
+    /// Decodes the CRC or digest stored after the block, undoing the output byte swap.
+    pub fn integrity_values(stored: &[u8], settings: &Settings) -> (u64, Option<Vec<u8>>) {
+        let mut bytes = stored.to_vec();
+        if settings.byte_swap {
+            for pair in bytes.chunks_exact_mut(2) {
+                pair.swap(0, 1);
+            }
+        }
+        match (&settings.digest, settings.endianness) {
+            (Some(_), _) => (0, Some(bytes)),
+            (None, Endianness::Big) => (bytes.iter().fold(0, |acc, &b| acc << 8 | b as u64), None),
+            (None, Endianness::Little) => (
+                bytes.iter().rev().fold(0, |acc, &b| acc << 8 | b as u64),
+                None,
+            ),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -34,4 +34,11 @@ pub struct LayoutArgs {
         default_value_t = false
     )]
     pub strict: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Build number inserted by fields with build = \"build_number\""
+    )]
+    pub build_number: Option<u64>,
 }
@@ -0,0 +1,130 @@
+use super::block::BuildConfig;
+use super::conversions::TryFromStrict;
+use super::entry::{EntrySource, ScalarType, evaluate_literal, retrieve_single_value};
+use super::errors::LayoutError;
+use super::value::{DataValue, ValueSource};
+use crate::variant::DataSheet;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+/// Single member of a bitfield, packed LSB-first into the storage word.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BitField {
+    pub bits: u32,
+    #[serde(flatten)]
+    pub source: EntrySource,
+    /// Enum used to resolve symbolic string values.
+    #[serde(rename = "enum")]
+    pub enum_name: Option<String>,
+}
+
+/// Packs the bitfield members into a single word of the given storage type.
+pub fn pack(
+    fields: &IndexMap<String, BitField>,
+    storage: ScalarType,
+    data_sheet: Option<&DataSheet>,
+    config: &BuildConfig,
+) -> Result<u64, LayoutError> {
+    let width = match storage {
+        ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64 => {
+            storage.size_bytes() as u32 * 8
+        }
+        ScalarType::OddInt(int) if !int.signed => int.bits,
+        _ => {
+            return Err(LayoutError::DataValueExportFailed(
+                "Bitfield storage must be an unsigned integer type.".to_string(),
+            ));
+        }
+    };
+
+    let mut word: u64 = 0;
+    let mut shift: u32 = 0;
+    for (field_name, field) in fields {
+        let raw = member_bits(field, shift, width, data_sheet, config).map_err(|e| {
+            LayoutError::InField {
+                field: field_name.clone(),
+                source: Box::new(e),
+            }
+        })?;
+
+        word |= raw << shift;
+        shift += field.bits;
+    }
+
+    Ok(word)
+}
+
+/// Resolves one member and masks it to its width, checking it fits at the given shift.
+fn member_bits(
+    field: &BitField,
+    shift: u32,
+    width: u32,
+    data_sheet: Option<&DataSheet>,
+    config: &BuildConfig,
+) -> Result<u64, LayoutError> {
+    let bits = field.bits;
+    if bits == 0 {
+        return Err(LayoutError::DataValueExportFailed(
+            "Bitfield member width must be at least 1 bit.".to_string(),
+        ));
+    }
+    if shift.checked_add(bits).is_none_or(|end| end > width) {
+        return Err(LayoutError::DataValueExportFailed(format!(
+            "Bitfield members exceed the {} bits of the storage type.",
+            width
+        )));
+    }
+
+    let mut value = field_value(&field.source, data_sheet, config)?;
+    if let Some(enum_name) = &field.enum_name {
+        value = value.resolve_enum(enum_name, config.enums)?;
+    } else if matches!(field.source, EntrySource::Value(_)) {
+        value = evaluate_literal(&value, data_sheet, config)?;
+    }
+    let raw = if config.strict {
+        <u64 as TryFromStrict<&DataValue>>::try_from_strict(&value)?
+    } else {
+        <u64 as TryFrom<&DataValue>>::try_from(&value)?
+    };
+
+    let mask = if bits == 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    };
+    if config.strict && raw > mask {
+        return Err(LayoutError::DataValueExportFailed(format!(
+            "value {} does not fit in {} bits",
+            raw, bits
+        )));
+    }
+    Ok(raw & mask)
+}
+
+fn field_value(
+    source: &EntrySource,
+    data_sheet: Option<&DataSheet>,
+    config: &BuildConfig,
+) -> Result<DataValue, LayoutError> {
+    match source {
+        EntrySource::Name(name) => retrieve_single_value(name, data_sheet, config),
+        EntrySource::Value(ValueSource::Single(v)) => Ok(v.clone()),
+        EntrySource::Value(_) => Err(LayoutError::DataValueExportFailed(
+            "Single value expected for bitfield member.".to_string(),
+        )),
+        EntrySource::Bitfield(_) => Err(LayoutError::DataValueExportFailed(
+            "Bitfields cannot be nested.".to_string(),
+        )),
+        EntrySource::Build(field) => config.build.value(*field),
+        EntrySource::Computed(_) | EntrySource::Checksum(_) => {
+            Err(LayoutError::DataValueExportFailed(
+                "Computed and checksum values are not supported in bitfield members.".to_string(),
+            ))
+        }
+        EntrySource::File(_) => Err(LayoutError::DataValueExportFailed(
+            "File contents are not supported in bitfield members.".to_string(),
+        )),
+    }
+}
@@ -1,71 +1,177 @@
-use super::entry::LeafEntry;
+use super::build_info::BuildInfo;
+use super::checksum::ChecksumAlgorithm;
+use super::entry::{EntrySource, LeafEntry, LiteralScope, ScalarType};
 use super::errors::LayoutError;
+use super::expr::{self, Scope};
 use super::header::{CrcLocation, Header};
-use super::settings::{Endianness, Settings};
-use crate::variant::DataSheet;
+use super::settings::{Endianness, Settings, SettingsOverride};
+use super::struct_array::StructArrayEntry;
+use super::types::TypeRef;
+use super::value::{DataValue, EnumTable};
+use crate::output::checksum::{calculate_crc, calculate_digest};
+use crate::variant::{DataSheet, TableRow};
 
 use indexmap::IndexMap;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 /// Mutable state tracked during recursive bytestream building
-struct BuildState {
+struct BuildState<'a> {
     buffer: Vec<u8>,
     offset: usize,
     padding_count: u32,
+    /// Path of the entry being built, e.g. "calibration.matrix" or "rows[2].gain".
+    path: String,
+    /// Byte range of every entry built so far, keyed by path.
+    ranges: HashMap<String, (usize, usize)>,
+    /// Computed and checksum leaves with their path and offset, filled in after
+    /// the first pass.
+    deferred: Vec<(String, usize, &'a LeafEntry)>,
+}
+
+impl BuildState<'_> {
+    /// Appends a field name or "[i]" index to the current path, returning the
+    /// length to truncate back to.
+    fn push_path(&mut self, segment: &str) -> usize {
+        let len = self.path.len();
+        if !self.path.is_empty() && !segment.starts_with('[') {
+            self.path.push('.');
+        }
+        self.path.push_str(segment);
+        len
+    }
+
+    fn record(&mut self, start: usize) {
+        self.ranges.insert(self.path.clone(), (start, self.offset));
+    }
 }
 
 /// Immutable configuration for bytestream building
+#[derive(Clone, Copy)]
 pub struct BuildConfig<'a> {
     pub endianness: &'a Endianness,
     pub padding: u8,
     pub strict: bool,
+    pub enums: &'a IndexMap<String, EnumTable>,
+    pub constants: &'a IndexMap<String, DataValue>,
+    pub build: &'a BuildInfo,
+    /// Directory that 'file' sources and key files are resolved against.
+    pub base_dir: &'a Path,
+    pub max_alignment: Option<usize>,
+    /// Struct array row that 'name' lookups resolve against, if inside an element.
+    pub row: Option<&'a TableRow>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub settings: Settings,
+    /// Named enumerations usable by leaf entries through the 'enum' key.
+    #[serde(default)]
+    pub enums: IndexMap<String, EnumTable>,
+    /// Named numeric constants usable in value expressions.
+    #[serde(default)]
+    pub constants: IndexMap<String, DataValue>,
+    /// Named composite types referenced from entries with `type = "<name>"`.
+    #[serde(default)]
+    pub types: IndexMap<String, Entry>,
     #[serde(flatten)]
     pub blocks: IndexMap<String, Block>,
+    /// Directory of the layout file, which relative paths in the layout refer to.
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+    /// Layout files read to build this config: the root file and its includes.
+    #[serde(skip)]
+    pub sources: Vec<PathBuf>,
 }
 
 /// Flash block.
 #[derive(Debug, Deserialize)]
 pub struct Block {
     pub header: Header,
+    /// Overrides of the layout-wide settings for this block only.
+    #[serde(default)]
+    pub settings: SettingsOverride,
     pub data: Entry,
 }
 
 /// Any entry - should always be either a leaf or a branch (more entries).
-#[derive(Debug, Deserialize)]
+/// Type references are expanded into leaves or branches when the layout is loaded.
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum Entry {
-    Leaf(LeafEntry),
-    Branch(IndexMap<String, Entry>),
+    Leaf(Box<LeafEntry>),
+    Type(TypeRef),
+    StructArray(StructArrayEntry),
+    Branch(BranchEntry),
+}
+
+/// Named child entries, optionally placed at a fixed offset within the block.
+/// The offset is given as '_offset' so that 'offset' stays free as a child name.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BranchEntry {
+    #[serde(rename = "_offset")]
+    pub offset: Option<usize>,
+    #[serde(flatten)]
+    pub fields: IndexMap<String, Entry>,
 }
 
 impl Block {
+    /// Layout settings with this block's overrides applied.
+    pub fn effective_settings(&self, layout: &Config) -> Settings {
+        let mut settings = layout.settings.with_overrides(&self.settings);
+        settings.digest = settings.digest.map(|d| d.relative_to(&layout.base_dir));
+        settings
+    }
+
     pub fn build_bytestream(
         &self,
         data_sheet: Option<&DataSheet>,
-        settings: &Settings,
+        layout: &Config,
         strict: bool,
+        build: &BuildInfo,
     ) -> Result<(Vec<u8>, u32), LayoutError> {
         let mut state = BuildState {
             buffer: Vec::with_capacity((self.header.length as usize).min(64 * 1024)),
             offset: 0,
             padding_count: 0,
+            path: String::new(),
+            ranges: HashMap::new(),
+            deferred: Vec::new(),
         };
+        let settings = self.effective_settings(layout);
         let config = BuildConfig {
             endianness: &settings.endianness,
             padding: self.header.padding,
             strict,
+            enums: &layout.enums,
+            constants: &layout.constants,
+            build,
+            base_dir: &layout.base_dir,
+            max_alignment: self.header.max_alignment,
+            row: None,
         };
 
+        if let Some(max) = config.max_alignment
+            && !max.is_power_of_two()
+        {
+            return Err(LayoutError::InvalidBlockArgument(format!(
+                "max_alignment {} is not a power of two",
+                max
+            )));
+        }
+
         Self::build_bytestream_inner(&self.data, data_sheet, &mut state, &config)?;
 
+        let base_address = self.header.start_address as u64 + settings.virtual_offset as u64;
+        Self::resolve_computed(&mut state, data_sheet, &config, base_address)?;
+        Self::resolve_checksums(&mut state, &config)?;
+
         if matches!(self.header.crc_location, CrcLocation::Keyword(_)) {
-            // Padding out to the 4 byte boundary for appended/prepended CRC32
-            while !state.offset.is_multiple_of(4) {
+            // Padding out to the alignment of the appended CRC or digest
+            let (_, slot_align) = settings.integrity_slot();
+            while !state.offset.is_multiple_of(slot_align as usize) {
                 state.buffer.push(config.padding);
                 state.offset += 1;
                 state.padding_count += 1;
@@ -75,36 +181,412 @@ impl Block {
         Ok((state.buffer, state.padding_count))
     }
 
-    fn build_bytestream_inner(
-        table: &Entry,
+    fn build_bytestream_inner<'a>(
+        table: &'a Entry,
         data_sheet: Option<&DataSheet>,
-        state: &mut BuildState,
+        state: &mut BuildState<'a>,
         config: &BuildConfig,
     ) -> Result<(), LayoutError> {
         match table {
             Entry::Leaf(leaf) => {
-                let alignment = leaf.get_alignment();
-                while !state.offset.is_multiple_of(alignment) {
-                    state.buffer.push(config.padding);
-                    state.offset += 1;
-                    state.padding_count += 1;
+                match leaf.offset {
+                    Some(offset) => Self::seek(offset, state, config)?,
+                    None => {
+                        let alignment = leaf.get_alignment(config.max_alignment)?;
+                        while !state.offset.is_multiple_of(alignment) {
+                            state.buffer.push(config.padding);
+                            state.offset += 1;
+                            state.padding_count += 1;
+                        }
+                    }
                 }
 
+                let start = state.offset;
                 let bytes = leaf.emit_bytes(data_sheet, config)?;
                 state.offset += bytes.len();
                 state.buffer.extend(bytes);
+                state.record(start);
+
+                if let EntrySource::Computed(_) | EntrySource::Checksum(_) = leaf.source {
+                    state.deferred.push((state.path.clone(), start, leaf));
+                }
+            }
+            Entry::Type(type_ref) => {
+                return Err(LayoutError::TypeNotFound(format!(
+                    "unexpanded reference to '{}'",
+                    type_ref.type_name
+                )));
+            }
+            Entry::StructArray(array) => {
+                if let Some(offset) = array.offset {
+                    Self::seek(offset, state, config)?;
+                }
+                Self::build_struct_array(array, data_sheet, state, config)?;
             }
             Entry::Branch(branch) => {
-                for (field_name, v) in branch.iter() {
-                    Self::build_bytestream_inner(v, data_sheet, state, config).map_err(|e| {
-                        LayoutError::InField {
-                            field: field_name.clone(),
-                            source: Box::new(e),
-                        }
+                if let Some(offset) = branch.offset {
+                    Self::seek(offset, state, config)?;
+                }
+                // A branch starts at its first member, after that member's alignment padding
+                let mut start = None;
+                for (field_name, v) in branch.fields.iter() {
+                    let len = state.push_path(field_name);
+                    let result = Self::build_bytestream_inner(v, data_sheet, state, config);
+                    if start.is_none() {
+                        start = state.ranges.get(&state.path).map(|range| range.0);
+                    }
+                    state.path.truncate(len);
+                    result.map_err(|e| LayoutError::InField {
+                        field: field_name.clone(),
+                        source: Box::new(e),
                     })?;
                 }
+                state.record(start.unwrap_or(state.offset));
             }
         }
         Ok(())
     }
+
+    fn build_struct_array<'a>(
+        array: &'a StructArrayEntry,
+        data_sheet: Option<&DataSheet>,
+        state: &mut BuildState<'a>,
+        config: &BuildConfig,
+    ) -> Result<(), LayoutError> {
+        let (count, strict_len) = array.count()?;
+        let Some(data_sheet) = data_sheet else {
+            return Err(LayoutError::MissingDataSheet(format!(
+                "Struct array '{}' requires rows from the Excel datasheet, but no datasheet was provided. Use -x to specify an Excel file.",
+                array.name
+            )));
+        };
+        let rows = data_sheet.retrieve_table(&array.name)?;
+
+        if rows.len() > count {
+            return Err(LayoutError::DataValueExportFailed(
+                "Struct array row count greater than defined size.".to_string(),
+            ));
+        }
+        if strict_len && rows.len() < count {
+            return Err(LayoutError::DataValueExportFailed(
+                "Struct array row count smaller than defined size (strict SIZE).".to_string(),
+            ));
+        }
+
+        // Elements are aligned to their widest member and padded to a multiple of it, as in C.
+        let alignment = array.element.alignment(config.max_alignment)?;
+        let mut stride = 0;
+        array.element.measure(&mut stride, config.max_alignment)?;
+        let stride = stride.next_multiple_of(alignment);
+
+        let mut array_start = None;
+        for i in 0..count {
+            let len = state.push_path(&format!("[{}]", i));
+            let result = (|| {
+                while !state.offset.is_multiple_of(alignment) {
+                    state.buffer.push(config.padding);
+                    state.offset += 1;
+                    state.padding_count += 1;
+                }
+                let start = state.offset;
+                array_start.get_or_insert(start);
+
+                match rows.get(i) {
+                    Some(row) => {
+                        let row_config = BuildConfig {
+                            row: Some(row),
+                            ..*config
+                        };
+                        Self::build_bytestream_inner(
+                            &array.element,
+                            Some(data_sheet),
+                            state,
+                            &row_config,
+                        )?;
+                    }
+                    None => {
+                        state
+                            .buffer
+                            .resize(state.buffer.len() + stride, config.padding);
+                        state.offset += stride;
+                        state.padding_count += stride as u32;
+                    }
+                }
+
+                while state.offset - start < stride {
+                    state.buffer.push(config.padding);
+                    state.offset += 1;
+                    state.padding_count += 1;
+                }
+                state.record(start);
+                Ok(())
+            })();
+            state.path.truncate(len);
+
+            result.map_err(|e| LayoutError::InField {
+                field: format!("[{}]", i),
+                source: Box::new(e),
+            })?;
+        }
+        state.record(array_start.unwrap_or(state.offset));
+        Ok(())
+    }
+
+    /// Second pass: evaluates computed leaves now that every entry's position is known
+    /// and overwrites their placeholder bytes.
+    fn resolve_computed(
+        state: &mut BuildState,
+        data_sheet: Option<&DataSheet>,
+        config: &BuildConfig,
+        base_address: u64,
+    ) -> Result<(), LayoutError> {
+        for (path, offset, leaf) in &state.deferred {
+            let EntrySource::Computed(src) = &leaf.source else {
+                continue;
+            };
+            let mut scope = LayoutScope {
+                ranges: &state.ranges,
+                base_address,
+                literal: LiteralScope { data_sheet, config },
+            };
+            let bytes = expr::evaluate(src, &mut scope)
+                .and_then(|value| leaf.value_to_bytes(&value, config))
+                .map_err(|e| LayoutError::InField {
+                    field: path.clone(),
+                    source: Box::new(e),
+                })?;
+            state.buffer[*offset..*offset + bytes.len()].copy_from_slice(&bytes);
+        }
+        Ok(())
+    }
+
+    /// Final pass: computes checksum leaves over the assembled bytes. A checksum stored
+    /// inside another checksum's region is computed first, so that region is complete.
+    fn resolve_checksums(state: &mut BuildState, config: &BuildConfig) -> Result<(), LayoutError> {
+        let mut jobs = Vec::new();
+        for (path, offset, leaf) in &state.deferred {
+            let (EntrySource::Checksum(spec), Some(over)) = (&leaf.source, &leaf.over) else {
+                continue;
+            };
+            let in_field = |e| LayoutError::InField {
+                field: path.clone(),
+                source: Box::new(e),
+            };
+
+            let algorithm = spec.algorithm().map_err(in_field)?;
+            let len = leaf.byte_len().map_err(in_field)?;
+            match &algorithm {
+                ChecksumAlgorithm::Crc(crc)
+                    if !matches!(
+                        leaf.scalar_type,
+                        ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64
+                    ) || leaf.scalar_type.size_bytes() != crc.width.bytes()
+                        || len != crc.width.bytes() =>
+                {
+                    return Err(in_field(LayoutError::DataValueExportFailed(format!(
+                        "CRC{} checksums must have type u{}",
+                        crc.width.bits(),
+                        crc.width.bits()
+                    ))));
+                }
+                ChecksumAlgorithm::Digest(_)
+                    if !matches!(leaf.scalar_type, ScalarType::U8) || len != 32 =>
+                {
+                    return Err(in_field(LayoutError::DataValueExportFailed(
+                        "Digests must have type u8 and size 32".to_string(),
+                    )));
+                }
+                _ => {}
+            }
+            let &(start, end) = state.ranges.get(over).ok_or_else(|| {
+                in_field(LayoutError::DataValueExportFailed(format!(
+                    "Unknown field '{}' in 'over'",
+                    over
+                )))
+            })?;
+            if *offset < end && start < offset + len {
+                return Err(in_field(LayoutError::DataValueExportFailed(format!(
+                    "Checksum lies inside the region '{}' it covers",
+                    over
+                ))));
+            }
+            jobs.push((path, *offset..offset + len, leaf, algorithm, start..end));
+        }
+
+        let slots_and_regions: Vec<_> = jobs
+            .iter()
+            .map(|(_, slot, _, _, region)| (slot.clone(), region.clone()))
+            .collect();
+        let order = checksum_order(&slots_and_regions).map_err(|cycle| {
+            let paths: Vec<&str> = cycle.iter().map(|&i| jobs[i].0.as_str()).collect();
+            LayoutError::DataValueExportFailed(format!(
+                "Checksums depend on each other in a cycle: {}",
+                paths.join(", ")
+            ))
+        })?;
+
+        for i in order {
+            let (path, slot, leaf, algorithm, region) = &jobs[i];
+            let in_field = |e| LayoutError::InField {
+                field: path.to_string(),
+                source: Box::new(e),
+            };
+            let bytes = match algorithm {
+                ChecksumAlgorithm::Crc(crc) => {
+                    let value = DataValue::U64(calculate_crc(&state.buffer[region.clone()], crc));
+                    leaf.value_to_bytes(&value, config).map_err(in_field)?
+                }
+                ChecksumAlgorithm::Digest(digest) => {
+                    let key = digest
+                        .relative_to(config.base_dir)
+                        .key()
+                        .map_err(in_field)?;
+                    calculate_digest(&state.buffer[region.clone()], digest.algorithm, &key).to_vec()
+                }
+            };
+            state.buffer[slot.clone()].copy_from_slice(&bytes);
+        }
+        Ok(())
+    }
+
+    /// Pads up to an explicit offset, failing if data has already been written past it.
+    fn seek(
+        offset: usize,
+        state: &mut BuildState,
+        config: &BuildConfig,
+    ) -> Result<(), LayoutError> {
+        if offset < state.offset {
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "Offset 0x{:X} is behind the current position 0x{:X}; fields would overlap",
+                offset, state.offset
+            )));
+        }
+        while state.offset < offset {
+            state.buffer.push(config.padding);
+            state.offset += 1;
+            state.padding_count += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Expression scope for computed fields: adds 'sizeof', 'offsetof' and 'address_of'
+/// over entry paths to the constants and Excel names available to literals.
+struct LayoutScope<'a> {
+    ranges: &'a HashMap<String, (usize, usize)>,
+    /// Absolute address of the first byte of the block.
+    base_address: u64,
+    literal: LiteralScope<'a>,
+}
+
+impl Scope for LayoutScope<'_> {
+    fn variable(&mut self, name: &str) -> Result<DataValue, LayoutError> {
+        self.literal.variable(name)
+    }
+
+    fn call(&mut self, name: &str, arg: &str) -> Result<DataValue, LayoutError> {
+        // An empty path refers to the whole block data
+        let range = |path: &str| {
+            self.ranges.get(path).copied().ok_or_else(|| {
+                LayoutError::DataValueExportFailed(format!(
+                    "Unknown field '{}' in {}()",
+                    path, name
+                ))
+            })
+        };
+        match name {
+            "sizeof" => range(arg).map(|(start, end)| DataValue::U64((end - start) as u64)),
+            "offsetof" => range(arg).map(|(start, _)| DataValue::U64(start as u64)),
+            "address_of" => {
+                range(arg).map(|(start, _)| DataValue::U64(self.base_address + start as u64))
+            }
+            _ => Err(LayoutError::DataValueExportFailed(format!(
+                "Unknown function '{}'; expected sizeof, offsetof or address_of",
+                name
+            ))),
+        }
+    }
+}
+
+impl Entry {
+    /// Returns the alignment of the entry: its own for leaves, the widest member otherwise.
+    pub fn alignment(&self, max_alignment: Option<usize>) -> Result<usize, LayoutError> {
+        match self {
+            Entry::Leaf(leaf) => leaf.get_alignment(max_alignment),
+            Entry::Type(_) => Ok(1),
+            Entry::StructArray(array) => array.element.alignment(max_alignment),
+            Entry::Branch(branch) => branch
+                .fields
+                .values()
+                .try_fold(1, |acc, v| Ok(acc.max(v.alignment(max_alignment)?))),
+        }
+    }
+
+    /// Advances `offset` by the bytes the entry occupies, including alignment padding.
+    /// Used to size struct array elements independently of the data they hold.
+    fn measure(&self, offset: &mut usize, max_alignment: Option<usize>) -> Result<(), LayoutError> {
+        match self {
+            Entry::Leaf(leaf) => {
+                if leaf.offset.is_some() {
+                    return Err(LayoutError::DataValueExportFailed(
+                        "Explicit offsets are not supported inside struct array elements."
+                            .to_string(),
+                    ));
+                }
+                *offset = offset.next_multiple_of(leaf.get_alignment(max_alignment)?);
+                *offset += leaf.byte_len()?;
+            }
+            Entry::Type(type_ref) => {
+                return Err(LayoutError::TypeNotFound(format!(
+                    "unexpanded reference to '{}'",
+                    type_ref.type_name
+                )));
+            }
+            Entry::StructArray(array) => {
+                let (count, _) = array.count()?;
+                let alignment = array.element.alignment(max_alignment)?;
+                let mut stride = 0;
+                array.element.measure(&mut stride, max_alignment)?;
+                *offset = offset.next_multiple_of(alignment);
+                *offset += stride.next_multiple_of(alignment) * count;
+            }
+            Entry::Branch(branch) => {
+                if branch.offset.is_some() {
+                    return Err(LayoutError::DataValueExportFailed(
+                        "Explicit offsets are not supported inside struct array elements."
+                            .to_string(),
+                    ));
+                }
+                for v in branch.fields.values() {
+                    v.measure(offset, max_alignment)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Orders checksum jobs, given as (slot, region) byte ranges, so that every checksum
+/// whose slot lies inside another job's region runs before that job. Jobs without
+/// dependencies keep their layout order. On a cycle, returns the jobs left unordered.
+fn checksum_order(jobs: &[(Range<usize>, Range<usize>)]) -> Result<Vec<usize>, Vec<usize>> {
+    let depends_on = |j: usize, i: usize| {
+        let ((slot, _), (_, region)) = (&jobs[i], &jobs[j]);
+        i != j && slot.start < region.end && region.start < slot.end
+    };
+
+    let mut done = vec![false; jobs.len()];
+    let mut order = Vec::with_capacity(jobs.len());
+    while order.len() < jobs.len() {
+        let ready = (0..jobs.len())
+            .find(|&j| !done[j] && (0..jobs.len()).all(|i| done[i] || !depends_on(j, i)));
+        match ready {
+            Some(j) => {
+                done[j] = true;
+                order.push(j);
+            }
+            None => return Err((0..jobs.len()).filter(|&i| !done[i]).collect()),
+        }
+    }
+    Ok(order)
 }
@@ -0,0 +1,243 @@
+use super::block::{Config, Entry};
+use super::checksum::ChecksumSpec;
+use super::entry::EntrySource;
+use super::errors::LayoutError;
+use super::value::DataValue;
+use crate::args::Args;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Build-time fact inserted by a leaf with a 'build' source.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildField {
+    /// Seconds since the Unix epoch (UTC).
+    Timestamp,
+    /// UTC timestamp as an ISO 8601 string, e.g. "2024-01-31T12:00:00Z".
+    TimestampIso,
+    /// Number given with --build-number.
+    BuildNumber,
+    /// Selected variant column, or "Default".
+    Variant,
+    /// 1 if the Debug column was selected, otherwise 0.
+    Debug,
+    /// Version of this tool as a string.
+    Version,
+    /// First four bytes of a SHA-256 over every file the build reads.
+    InputHash,
+}
+
+/// Facts about the current build, shared by every block.
+#[derive(Debug, Clone, Default)]
+pub struct BuildInfo {
+    pub timestamp: u64,
+    pub build_number: Option<u64>,
+    pub variant: Option<String>,
+    pub debug: bool,
+    pub input_hash: u32,
+}
+
+impl BuildInfo {
+    /// Collects build facts from the command line. The timestamp is taken from
+    /// SOURCE_DATE_EPOCH when set, so that builds are reproducible. The timestamp and
+    /// input hash are only worked out when a requested block uses them.
+    pub fn from_args(args: &Args) -> Result<Self, LayoutError> {
+        let mut inputs = BuildInputs::default();
+        let mut layouts: Vec<(&str, Config)> = Vec::new();
+        for block in &args.layout.blocks {
+            if !layouts.iter().any(|(file, _)| *file == block.file) {
+                // Layouts that fail to load are reported when their blocks are built
+                let Ok(layout) = super::load_layout(&block.file) else {
+                    continue;
+                };
+                for source in &layout.sources {
+                    inputs.add_file(source.clone(), &layout.base_dir);
+                }
+                layouts.push((&block.file, layout));
+            }
+            let Some((_, layout)) = layouts.iter().find(|(file, _)| *file == block.file) else {
+                continue;
+            };
+            if let Some(data) = layout.blocks.get(&block.name) {
+                inputs.add_entry(&data.data, &layout.base_dir);
+                if let Some(key) = data
+                    .effective_settings(layout)
+                    .digest
+                    .and_then(|d| d.key_file)
+                {
+                    inputs.add_file(key, &layout.base_dir);
+                }
+            }
+        }
+        if let Some(xlsx) = &args.variant.xlsx {
+            let xlsx = PathBuf::from(xlsx);
+            let dir = xlsx.parent().unwrap_or(Path::new("")).to_path_buf();
+            inputs.add_file(xlsx, &dir);
+        }
+
+        let timestamp =
+            if inputs.uses(BuildField::Timestamp) || inputs.uses(BuildField::TimestampIso) {
+                match std::env::var("SOURCE_DATE_EPOCH") {
+                    Ok(epoch) => epoch.trim().parse::<u64>().map_err(|_| {
+                        LayoutError::BuildInfo(format!("invalid SOURCE_DATE_EPOCH '{}'", epoch))
+                    })?,
+                    Err(_) => SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0),
+                }
+            } else {
+                0
+            };
+        let input_hash = if inputs.uses(BuildField::InputHash) {
+            hash_files(&inputs.files)?
+        } else {
+            0
+        };
+
+        Ok(BuildInfo {
+            timestamp,
+            build_number: args.layout.build_number,
+            variant: args.variant.variant.clone(),
+            debug: args.variant.debug,
+            input_hash,
+        })
+    }
+
+    pub fn value(&self, field: BuildField) -> Result<DataValue, LayoutError> {
+        Ok(match field {
+            BuildField::Timestamp => DataValue::U64(self.timestamp),
+            BuildField::TimestampIso => DataValue::Str(iso_timestamp(self.timestamp)),
+            BuildField::BuildNumber => DataValue::U64(self.build_number.ok_or_else(|| {
+                LayoutError::BuildInfo("no build number given; use --build-number".to_string())
+            })?),
+            BuildField::Variant => {
+                DataValue::Str(self.variant.clone().unwrap_or("Default".to_string()))
+            }
+            BuildField::Debug => DataValue::U64(self.debug as u64),
+            BuildField::Version => DataValue::Str(env!("CARGO_PKG_VERSION").to_string()),
+            BuildField::InputHash => DataValue::U64(self.input_hash as u64),
+        })
+    }
+}
+
+/// Files and build fields the requested blocks depend on.
+#[derive(Default)]
+struct BuildInputs {
+    /// Each file with the key it is hashed under: its path relative to the directory it was
+    /// named from, so the hash does not depend on where the checkout is or how it was spelled.
+    files: Vec<(PathBuf, PathBuf)>,
+    fields: Vec<BuildField>,
+}
+
+impl BuildInputs {
+    fn uses(&self, field: BuildField) -> bool {
+        self.fields.contains(&field)
+    }
+
+    fn add_file(&mut self, path: PathBuf, base_dir: &Path) {
+        if !self.files.iter().any(|(file, _)| *file == path) {
+            let key = path.strip_prefix(base_dir).unwrap_or(&path).to_path_buf();
+            self.files.push((path, key));
+        }
+    }
+
+    fn add_entry(&mut self, entry: &Entry, base_dir: &Path) {
+        match entry {
+            Entry::Leaf(leaf) => self.add_source(&leaf.source, base_dir),
+            Entry::Branch(branch) => {
+                for child in branch.fields.values() {
+                    self.add_entry(child, base_dir);
+                }
+            }
+            Entry::StructArray(array) => self.add_entry(&array.element, base_dir),
+            // Type references are expanded when the layout is loaded
+            Entry::Type(_) => {}
+        }
+    }
+
+    fn add_source(&mut self, source: &EntrySource, base_dir: &Path) {
+        match source {
+            EntrySource::File(path) => self.add_file(base_dir.join(path), base_dir),
+            EntrySource::Build(field) => self.fields.push(*field),
+            EntrySource::Bitfield(members) => {
+                for member in members.values() {
+                    self.add_source(&member.source, base_dir);
+                }
+            }
+            EntrySource::Checksum(ChecksumSpec::Digest(digest)) => {
+                if let Some(key) = &digest.key_file {
+                    self.add_file(base_dir.join(key), base_dir);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// SHA-256 over each file's key, length and contents, truncated to 32 bits.
+fn hash_files(files: &[(PathBuf, PathBuf)]) -> Result<u32, LayoutError> {
+    let mut hasher = Sha256::new();
+    for (file, key) in files {
+        let bytes = std::fs::read(file).map_err(|_| {
+            LayoutError::FileError(format!("failed to open file: {}", file.display()))
+        })?;
+        // Joined with '/' so the key is the same on every platform
+        let path = key
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        hasher.update((path.len() as u64).to_le_bytes());
+        hasher.update(path.as_bytes());
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    let digest = hasher.finalize();
+    Ok(u32::from_be_bytes([
+        digest[0], digest[1], digest[2], digest[3],
+    ]))
+}
+
+/// Formats seconds since the Unix epoch as "YYYY-MM-DDTHH:MM:SSZ".
+fn iso_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil date from days since 1970-01-01, after H. Hinnant's days_from_civil inverse
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso_timestamps() {
+        assert_eq!(iso_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(iso_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(iso_timestamp(4_102_444_799), "2099-12-31T23:59:59Z");
+    }
+}
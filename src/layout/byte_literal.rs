@@ -0,0 +1,137 @@
+use super::errors::LayoutError;
+
+/// Decodes a "hex:" or "base64:" byte literal. Returns None for any other string.
+/// Whitespace is ignored in both forms, and hex digits may also be separated by
+/// ':' or '_', e.g. "hex:00:1A:2B:3C:4D:5E".
+pub fn decode(text: &str) -> Option<Result<Vec<u8>, LayoutError>> {
+    if let Some(digits) = text.strip_prefix("hex:") {
+        Some(decode_hex(digits))
+    } else {
+        text.strip_prefix("base64:").map(decode_base64)
+    }
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>, LayoutError> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && *c != ':' && *c != '_')
+        .map(|c| {
+            c.to_digit(16).ok_or_else(|| {
+                LayoutError::DataValueExportFailed(format!(
+                    "Invalid hex digit '{}' in byte literal",
+                    c
+                ))
+            })
+        })
+        .collect::<Result<Vec<u32>, _>>()?;
+
+    if !digits.len().is_multiple_of(2) {
+        return Err(LayoutError::DataValueExportFailed(
+            "Hex byte literal has an odd number of digits".to_string(),
+        ));
+    }
+    Ok(digits
+        .chunks_exact(2)
+        .map(|pair| (pair[0] << 4 | pair[1]) as u8)
+        .collect())
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, LayoutError> {
+    let text: String = text.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let body = text.trim_end_matches('=');
+    // Padding is optional, but when given it must fill the last group of four exactly
+    if text.len() != body.len() && (text.len() - body.len() > 2 || !text.len().is_multiple_of(4)) {
+        return Err(LayoutError::DataValueExportFailed(
+            "Incorrect padding in base64 byte literal".to_string(),
+        ));
+    }
+
+    let mut out = Vec::with_capacity(body.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for c in body.chars() {
+        let v = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' => 62,
+            '/' => 63,
+            _ => {
+                return Err(LayoutError::DataValueExportFailed(format!(
+                    "Invalid base64 character '{}' in byte literal",
+                    c
+                )));
+            }
+        };
+        acc = (acc << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+
+    // A single leftover character cannot encode a whole byte
+    if bits >= 6 {
+        return Err(LayoutError::DataValueExportFailed(
+            "Truncated base64 byte literal".to_string(),
+        ));
+    }
+    // Bits left over after the last byte must be zero in canonical encoding
+    if acc != 0 {
+        return Err(LayoutError::DataValueExportFailed(
+            "Non-canonical base64 byte literal".to_string(),
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(text: &str) -> Vec<u8> {
+        decode(text).expect("byte literal").expect("decodes")
+    }
+
+    #[test]
+    fn hex_literals() {
+        assert_eq!(ok("hex:DEADbeef0102"), [0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x02]);
+        assert_eq!(ok("hex:00:1A:2B 3C_4D"), [0x00, 0x1A, 0x2B, 0x3C, 0x4D]);
+        assert_eq!(ok("hex:"), Vec::<u8>::new());
+        assert!(decode("hex:ABC").unwrap().is_err());
+        assert!(decode("hex:0G").unwrap().is_err());
+    }
+
+    #[test]
+    fn base64_literals() {
+        assert_eq!(ok("base64:TWFu"), b"Man");
+        assert_eq!(ok("base64:TWE="), b"Ma");
+        assert_eq!(ok("base64:TQ=="), b"M");
+        assert_eq!(ok("base64:TQ"), b"M");
+        assert_eq!(ok("base64:3q2+7w=="), [0xDE, 0xAD, 0xBE, 0xEF]);
+        assert!(decode("base64:T").unwrap().is_err());
+        assert!(decode("base64:TQ===").unwrap().is_err());
+        assert!(decode("base64:T*Q=").unwrap().is_err());
+    }
+
+    #[test]
+    fn non_canonical_base64_is_rejected() {
+        // Padding that does not complete a group of four
+        assert!(decode("base64:TQ=").unwrap().is_err());
+        assert!(decode("base64:TWE==").unwrap().is_err());
+        assert!(decode("base64:TWFu=").unwrap().is_err());
+        assert!(decode("base64:TWFu====").unwrap().is_err());
+        // Non-zero bits after the last whole byte
+        assert!(decode("base64:TR==").unwrap().is_err());
+        assert!(decode("base64:TWF=").unwrap().is_err());
+        assert!(decode("base64:TWF").unwrap().is_err());
+    }
+
+    #[test]
+    fn other_strings_are_not_byte_literals() {
+        assert!(decode("DEADBEEF").is_none());
+        assert!(decode("HEX:00").is_none());
+    }
+}
@@ -0,0 +1,45 @@
+use super::crc_catalogue;
+use super::errors::LayoutError;
+use super::settings::{self, CrcArea, CrcData, DigestAlgorithm, DigestData};
+
+use serde::Deserialize;
+
+/// Algorithm of a checksum leaf: 'sha256', a catalogued CRC name such as "crc32" or
+/// "CRC-16/XMODEM", CRC parameters written like the 'settings.crc' table, or a digest
+/// written like 'settings.digest'.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ChecksumSpec {
+    Preset(String),
+    Crc(#[serde(deserialize_with = "settings::deserialize_leaf_crc")] CrcData),
+    Digest(DigestData),
+}
+
+/// Checksum algorithm with presets expanded.
+pub enum ChecksumAlgorithm {
+    Crc(CrcData),
+    Digest(DigestData),
+}
+
+impl ChecksumSpec {
+    pub fn algorithm(&self) -> Result<ChecksumAlgorithm, LayoutError> {
+        match self {
+            ChecksumSpec::Crc(crc) => Ok(ChecksumAlgorithm::Crc(crc.clone())),
+            ChecksumSpec::Digest(digest) => Ok(ChecksumAlgorithm::Digest(digest.clone())),
+            ChecksumSpec::Preset(name) => match name.as_str() {
+                "sha256" => Ok(ChecksumAlgorithm::Digest(DigestData {
+                    algorithm: DigestAlgorithm::Sha256,
+                    key_file: None,
+                    area: CrcArea::Data,
+                })),
+                _ => match crc_catalogue::find(name) {
+                    Some(entry) => Ok(ChecksumAlgorithm::Crc(entry.crc.clone())),
+                    None => Err(LayoutError::DataValueExportFailed(format!(
+                        "Unknown checksum '{}'; expected a CRC preset such as 'CRC-32/ISO-HDLC', 'sha256' or a table of CRC or digest parameters",
+                        name
+                    ))),
+                },
+            },
+        }
+    }
+}
@@ -1,8 +1,10 @@
-use super::entry::ScalarType;
+use super::entry::{FixedPoint, OddInt, ScalarType};
 use super::errors::LayoutError;
-use super::settings::{EndianBytes, Endianness};
+use super::settings::{EndianBytes, Endianness, truncated_endian_bytes};
 use super::value::DataValue;
 
+use half::{bf16, f16};
+
 macro_rules! impl_try_from_data_value {
     ($($t:ty),* $(,)?) => {$(
         impl TryFrom<&DataValue> for $t {
@@ -170,6 +172,7 @@ pub fn convert_value_to_bytes(
     scalar_type: ScalarType,
     endianness: &Endianness,
     strict: bool,
+    tolerance: Option<f64>,
 ) -> Result<Vec<u8>, LayoutError> {
     macro_rules! to_bytes {
         ($t:ty) => {{
@@ -191,7 +194,147 @@ pub fn convert_value_to_bytes(
         ScalarType::I32 => to_bytes!(i32),
         ScalarType::U64 => to_bytes!(u64),
         ScalarType::I64 => to_bytes!(i64),
+        ScalarType::F16 | ScalarType::BF16 => {
+            let bits = half_float_bits(value, scalar_type, strict, tolerance)?;
+            Ok(bits.to_endian_bytes(endianness))
+        }
         ScalarType::F32 => to_bytes!(f32),
         ScalarType::F64 => to_bytes!(f64),
+        ScalarType::Fixed(q) => {
+            let raw = fixed_point_raw(value, q, strict)?;
+            Ok(truncated_endian_bytes(
+                raw as u64,
+                q.bits as usize / 8,
+                endianness,
+            ))
+        }
+        ScalarType::OddInt(int) => {
+            let raw = odd_int_raw(value, int, strict)?;
+            Ok(truncated_endian_bytes(
+                raw as u64,
+                int.bits as usize / 8,
+                endianness,
+            ))
+        }
+    }
+}
+
+/// Rounds a value to a 16-bit float. Overflow gives infinity in lenient mode; strict
+/// mode rejects it, and rejects values whose relative error exceeds the tolerance.
+/// The default tolerance is the format's rounding error, so only values that lose
+/// precision as subnormals or underflow to zero fail.
+fn half_float_bits(
+    value: &DataValue,
+    scalar_type: ScalarType,
+    strict: bool,
+    tolerance: Option<f64>,
+) -> Result<u16, LayoutError> {
+    let v = match value {
+        DataValue::U64(v) => *v as f64,
+        DataValue::I64(v) => *v as f64,
+        DataValue::F64(v) => *v,
+        DataValue::Str(_) => return Err(err!("Cannot convert string to scalar type.")),
+    };
+
+    let (name, bits, stored, rounding) = match scalar_type {
+        ScalarType::BF16 => {
+            let h = bf16::from_f64(v);
+            (
+                "bf16",
+                h.to_bits(),
+                h.to_f64(),
+                bf16::EPSILON.to_f64() / 2.0,
+            )
+        }
+        _ => {
+            let h = f16::from_f64(v);
+            ("f16", h.to_bits(), h.to_f64(), f16::EPSILON.to_f64() / 2.0)
+        }
+    };
+
+    if strict {
+        if !v.is_finite() {
+            return Err(err!("non-finite float not allowed in strict mode"));
+        }
+        if !stored.is_finite() {
+            return Err(err!(format!("float value {} out of range for {}", v, name)));
+        }
+        let tolerance = tolerance.unwrap_or(rounding);
+        if (stored - v).abs() > tolerance * v.abs() {
+            return Err(err!(format!(
+                "value {} is stored as {} in {}, beyond the relative tolerance {}",
+                v, stored, name, tolerance
+            )));
+        }
     }
+
+    Ok(bits)
+}
+
+/// Converts a value to an odd-width integer, rejecting out-of-range or inexact values
+/// in strict mode. Lenient mode follows the native `as` casts: integers wrap to the
+/// width and floats saturate at the type's range.
+fn odd_int_raw(value: &DataValue, int: OddInt, strict: bool) -> Result<i128, LayoutError> {
+    let (min, max) = int.range();
+    if strict {
+        let v = if int.signed {
+            <i64 as TryFromStrict<&DataValue>>::try_from_strict(value)? as i128
+        } else {
+            <u64 as TryFromStrict<&DataValue>>::try_from_strict(value)? as i128
+        };
+        if v < min || v > max {
+            return Err(err!(format!("value {} out of range for {}", v, int)));
+        }
+        Ok(v)
+    } else {
+        // The caller truncates the raw value to the type's bytes
+        match value {
+            DataValue::U64(v) => Ok(*v as i128),
+            DataValue::I64(v) => Ok(*v as i128),
+            DataValue::F64(v) => Ok((*v as i128).clamp(min, max)),
+            DataValue::Str(_) => Err(err!("Cannot convert string to scalar type.")),
+        }
+    }
+}
+
+/// Scales a value by 2^frac_bits, saturating in lenient mode and rejecting
+/// overflow or precision loss in strict mode.
+fn fixed_point_raw(value: &DataValue, q: FixedPoint, strict: bool) -> Result<i128, LayoutError> {
+    let v = match value {
+        DataValue::U64(v) => *v as f64,
+        DataValue::I64(v) => *v as f64,
+        DataValue::F64(v) => *v,
+        DataValue::Str(_) => return Err(err!("Cannot convert string to scalar type.")),
+    };
+
+    let (min, max) = if q.signed {
+        (-(1i128 << (q.bits - 1)), (1i128 << (q.bits - 1)) - 1)
+    } else {
+        (0, (1i128 << q.bits) - 1)
+    };
+    let scaled = v * 2f64.powi(q.frac_bits as i32);
+
+    if strict {
+        if !scaled.is_finite() {
+            return Err(err!(
+                "non-finite float cannot convert to fixed-point in strict mode"
+            ));
+        }
+        if scaled.fract() != 0.0 {
+            return Err(err!(format!(
+                "value {} is not exactly representable with {} fractional bits",
+                v, q.frac_bits
+            )));
+        }
+        // Compared as integers: (2^63 - 1) and (2^64 - 1) round up to the next power of two as f64
+        let raw = scaled as i128;
+        if raw < min || raw > max {
+            return Err(err!(format!(
+                "value {} out of range for fixed-point type",
+                v
+            )));
+        }
+    }
+
+    Ok((scaled.round() as i128).clamp(min, max))
 }
@@ -0,0 +1,100 @@
+use super::settings::{CrcArea, CrcData, CrcWidth};
+
+/// CRC algorithm from the CRC RevEng catalogue, with its check value over "123456789".
+pub struct CatalogueEntry {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub crc: CrcData,
+    pub check: u64,
+}
+
+/// Looks up a preset by name or alias, ignoring case.
+pub fn find(name: &str) -> Option<&'static CatalogueEntry> {
+    CATALOGUE.iter().find(|entry| {
+        entry.name.eq_ignore_ascii_case(name)
+            || entry.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Names of all presets, for error messages.
+pub fn names() -> Vec<&'static str> {
+    CATALOGUE.iter().map(|entry| entry.name).collect()
+}
+
+macro_rules! preset {
+    ($name:literal $(, $alias:literal)*; $width:ident, $poly:literal, $start:literal, $reflect:literal, $xor_out:literal, $check:literal) => {
+        CatalogueEntry {
+            name: $name,
+            aliases: &[$($alias),*],
+            crc: CrcData {
+                width: CrcWidth::$width,
+                polynomial: $poly,
+                start: $start,
+                xor_out: $xor_out,
+                ref_in: $reflect,
+                ref_out: $reflect,
+                area: CrcArea::Data,
+            },
+            check: $check,
+        }
+    };
+}
+
+#[rustfmt::skip]
+pub const CATALOGUE: &[CatalogueEntry] = &[
+    preset!("CRC-8/AUTOSAR"; W8, 0x2F, 0xFF, false, 0xFF, 0xDF),
+    preset!("CRC-8/BLUETOOTH"; W8, 0xA7, 0x00, true, 0x00, 0x26),
+    preset!("CRC-8/I-432-1", "CRC-8/ITU"; W8, 0x07, 0x00, false, 0x55, 0xA1),
+    preset!("CRC-8/MAXIM-DOW", "CRC-8/MAXIM"; W8, 0x31, 0x00, true, 0x00, 0xA1),
+    preset!("CRC-8/ROHC"; W8, 0x07, 0xFF, true, 0x00, 0xD0),
+    preset!("CRC-8/SAE-J1850"; W8, 0x1D, 0xFF, false, 0xFF, 0x4B),
+    preset!("CRC-8/SMBUS", "CRC-8"; W8, 0x07, 0x00, false, 0x00, 0xF4),
+    preset!("CRC-16/ARC", "CRC-16"; W16, 0x8005, 0x0000, true, 0x0000, 0xBB3D),
+    preset!("CRC-16/GENIBUS"; W16, 0x1021, 0xFFFF, false, 0xFFFF, 0xD64E),
+    preset!("CRC-16/IBM-3740", "CRC-16/CCITT-FALSE", "CRC-16/AUTOSAR"; W16, 0x1021, 0xFFFF, false, 0x0000, 0x29B1),
+    preset!("CRC-16/IBM-SDLC", "CRC-16/X-25"; W16, 0x1021, 0xFFFF, true, 0xFFFF, 0x906E),
+    preset!("CRC-16/KERMIT", "CRC-16/CCITT"; W16, 0x1021, 0x0000, true, 0x0000, 0x2189),
+    preset!("CRC-16/MAXIM-DOW"; W16, 0x8005, 0x0000, true, 0xFFFF, 0x44C2),
+    preset!("CRC-16/MODBUS"; W16, 0x8005, 0xFFFF, true, 0x0000, 0x4B37),
+    preset!("CRC-16/SPI-FUJITSU", "CRC-16/AUG-CCITT"; W16, 0x1021, 0x1D0F, false, 0x0000, 0xE5CC),
+    preset!("CRC-16/USB"; W16, 0x8005, 0xFFFF, true, 0xFFFF, 0xB4C8),
+    preset!("CRC-16/XMODEM"; W16, 0x1021, 0x0000, false, 0x0000, 0x31C3),
+    preset!("CRC-32/AUTOSAR"; W32, 0xF4ACFB13, 0xFFFFFFFF, true, 0xFFFFFFFF, 0x1697D06A),
+    preset!("CRC-32/BASE91-D", "CRC-32D"; W32, 0xA833982B, 0xFFFFFFFF, true, 0xFFFFFFFF, 0x87315576),
+    preset!("CRC-32/BZIP2"; W32, 0x04C11DB7, 0xFFFFFFFF, false, 0xFFFFFFFF, 0xFC891918),
+    preset!("CRC-32/CKSUM", "CRC-32/POSIX"; W32, 0x04C11DB7, 0x00000000, false, 0xFFFFFFFF, 0x765E7680),
+    preset!("CRC-32/ISCSI", "CRC-32C"; W32, 0x1EDC6F41, 0xFFFFFFFF, true, 0xFFFFFFFF, 0xE3069283),
+    preset!("CRC-32/ISO-HDLC", "CRC-32", "CRC32"; W32, 0x04C11DB7, 0xFFFFFFFF, true, 0xFFFFFFFF, 0xCBF43926),
+    preset!("CRC-32/JAMCRC"; W32, 0x04C11DB7, 0xFFFFFFFF, true, 0x00000000, 0x340BC6D9),
+    preset!("CRC-32/MPEG-2"; W32, 0x04C11DB7, 0xFFFFFFFF, false, 0x00000000, 0x0376E6E7),
+    preset!("CRC-64/ECMA-182"; W64, 0x42F0E1EBA9EA3693, 0x0000000000000000, false, 0x0000000000000000, 0x6C40DF5F0B497347),
+    preset!("CRC-64/GO-ISO"; W64, 0x000000000000001B, 0xFFFFFFFFFFFFFFFF, true, 0xFFFFFFFFFFFFFFFF, 0xB90956C775A41001),
+    preset!("CRC-64/WE"; W64, 0x42F0E1EBA9EA3693, 0xFFFFFFFFFFFFFFFF, false, 0xFFFFFFFFFFFFFFFF, 0x62EC59E3F1A4F00A),
+    preset!("CRC-64/XZ", "CRC-64/GO-ECMA"; W64, 0x42F0E1EBA9EA3693, 0xFFFFFFFFFFFFFFFF, true, 0xFFFFFFFFFFFFFFFF, 0x995DC9BBDF1939FA),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::checksum::calculate_crc;
+
+    #[test]
+    fn every_preset_matches_its_check_value() {
+        for entry in CATALOGUE {
+            assert_eq!(
+                calculate_crc(b"123456789", &entry.crc),
+                entry.check,
+                "{} check value failed",
+                entry.name
+            );
+        }
+    }
+
+    #[test]
+    fn presets_are_found_by_name_or_alias() {
+        assert_eq!(find("CRC-32/ISO-HDLC").unwrap().check, 0xCBF43926);
+        assert_eq!(find("crc-16/ccitt-false").unwrap().name, "CRC-16/IBM-3740");
+        assert_eq!(find("crc32").unwrap().name, "CRC-32/ISO-HDLC");
+        assert!(find("CRC-12/DECT").is_none());
+    }
+}
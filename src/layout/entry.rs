@@ -1,11 +1,20 @@
+use super::bitfield::{self, BitField};
 use super::block::BuildConfig;
+use super::build_info::BuildField;
+use super::byte_literal;
+use super::checksum::ChecksumSpec;
 use super::errors::LayoutError;
-use super::value::ValueSource;
+use super::expr;
+use super::scaling::Scaling;
+use super::value::{DataValue, StringEncoding, ValueSource};
 use crate::variant::DataSheet;
+
+use indexmap::IndexMap;
 use serde::Deserialize;
+use std::path::{Path, PathBuf};
 
 /// Leaf entry representing an item to add to the flash block.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct LeafEntry {
     #[serde(rename = "type")]
@@ -14,31 +23,172 @@ pub struct LeafEntry {
     size_keys: SizeKeys,
     #[serde(flatten)]
     pub source: EntrySource,
+    /// Enum used to resolve symbolic string values.
+    #[serde(rename = "enum")]
+    pub enum_name: Option<String>,
+    /// Optional physical-to-raw conversion applied before type conversion.
+    pub scaling: Option<Scaling>,
+    /// Inclusive lower bound checked on every element before scaling.
+    pub min: Option<f64>,
+    /// Inclusive upper bound checked on every element before scaling.
+    pub max: Option<f64>,
+    /// Exhaustive list of permitted values, checked before scaling.
+    pub allowed: Option<Vec<DataValue>>,
+    /// Fixed byte offset from the start of the block; overrides alignment.
+    pub offset: Option<usize>,
+    /// Alignment override; takes precedence over the block's max_alignment.
+    pub align: Option<usize>,
+    /// Encoding of string values; only valid on 1D arrays.
+    pub encoding: Option<StringEncoding>,
+    /// Path of the entry a 'checksum' leaf is computed over.
+    pub over: Option<String>,
+    /// Largest relative rounding error accepted for f16/bf16 values in strict mode.
+    pub tolerance: Option<f64>,
+    /// First byte of a 'file' source to insert.
+    pub file_offset: Option<usize>,
+    /// Number of bytes of a 'file' source to insert; defaults to the rest of the file.
+    pub file_length: Option<usize>,
 }
 
 /// Scalar type enum derived from 'type' string in leaf entries.
 #[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
 pub enum ScalarType {
-    #[serde(rename = "u8")]
     U8,
-    #[serde(rename = "u16")]
     U16,
-    #[serde(rename = "u32")]
     U32,
-    #[serde(rename = "u64")]
     U64,
-    #[serde(rename = "i8")]
     I8,
-    #[serde(rename = "i16")]
     I16,
-    #[serde(rename = "i32")]
     I32,
-    #[serde(rename = "i64")]
     I64,
-    #[serde(rename = "f32")]
+    F16,
+    BF16,
     F32,
-    #[serde(rename = "f64")]
     F64,
+    Fixed(FixedPoint),
+    OddInt(OddInt),
+}
+
+/// Integer stored in a whole number of bytes that is not a native width,
+/// e.g. 'u24' or 'i48'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OddInt {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+/// Fixed-point Q-format: 'qN'/'qM.N' are signed (M includes the sign bit),
+/// 'uqN'/'uqM.N' are unsigned. N is the number of fractional bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPoint {
+    pub signed: bool,
+    pub bits: u32,
+    pub frac_bits: u32,
+}
+
+impl TryFrom<String> for ScalarType {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Ok(match name.as_str() {
+            "u8" => ScalarType::U8,
+            "u16" => ScalarType::U16,
+            "u32" => ScalarType::U32,
+            "u64" => ScalarType::U64,
+            "i8" => ScalarType::I8,
+            "i16" => ScalarType::I16,
+            "i32" => ScalarType::I32,
+            "i64" => ScalarType::I64,
+            "f16" => ScalarType::F16,
+            "bf16" => ScalarType::BF16,
+            "f32" => ScalarType::F32,
+            "f64" => ScalarType::F64,
+            _ => match OddInt::parse(&name)? {
+                Some(int) => ScalarType::OddInt(int),
+                None => ScalarType::Fixed(FixedPoint::parse(&name)?),
+            },
+        })
+    }
+}
+
+impl OddInt {
+    /// Parses 'uN'/'iN'; returns None if the name is not an integer type at all.
+    fn parse(name: &str) -> Result<Option<Self>, String> {
+        let (signed, bits) = match name.split_at_checked(1) {
+            Some(("u", bits)) => (false, bits),
+            Some(("i", bits)) => (true, bits),
+            _ => return Ok(None),
+        };
+        let Ok(bits) = bits.parse::<u32>() else {
+            return Ok(None);
+        };
+
+        if bits == 0 || bits > 64 || !bits.is_multiple_of(8) {
+            return Err(format!(
+                "integer type '{}' must be a whole number of bytes up to 64 bits",
+                name
+            ));
+        }
+        Ok(Some(OddInt { signed, bits }))
+    }
+
+    /// Inclusive range of representable values.
+    pub fn range(&self) -> (i128, i128) {
+        if self.signed {
+            (-(1i128 << (self.bits - 1)), (1i128 << (self.bits - 1)) - 1)
+        } else {
+            (0, (1i128 << self.bits) - 1)
+        }
+    }
+}
+
+impl std::fmt::Display for OddInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+}
+
+impl FixedPoint {
+    fn parse(name: &str) -> Result<Self, String> {
+        let unknown = || format!("unknown type '{}'", name);
+
+        let (signed, spec) = if let Some(spec) = name.strip_prefix("uq") {
+            (false, spec)
+        } else if let Some(spec) = name.strip_prefix('q') {
+            (true, spec)
+        } else {
+            return Err(unknown());
+        };
+
+        let (int_bits, frac_bits) = match spec.split_once('.') {
+            Some((int_bits, frac_bits)) => (
+                int_bits.parse::<u32>().map_err(|_| unknown())?,
+                frac_bits.parse::<u32>().map_err(|_| unknown())?,
+            ),
+            None => (signed as u32, spec.parse::<u32>().map_err(|_| unknown())?),
+        };
+
+        if signed && int_bits == 0 {
+            return Err(format!(
+                "signed type '{}' needs at least the sign bit",
+                name
+            ));
+        }
+        let Some(bits @ (8 | 16 | 32 | 64)) = int_bits.checked_add(frac_bits) else {
+            return Err(format!(
+                "fixed-point type '{}' has {} bits; expected 8, 16, 32 or 64",
+                name,
+                int_bits as u64 + frac_bits as u64
+            ));
+        };
+
+        Ok(FixedPoint {
+            signed,
+            bits,
+            frac_bits,
+        })
+    }
 }
 
 /// Size source enum.
@@ -50,8 +200,8 @@ pub enum SizeSource {
 }
 
 /// Helper struct to capture both 'size' and 'SIZE' keys.
-#[derive(Debug, Default, Deserialize)]
-struct SizeKeys {
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(super) struct SizeKeys {
     #[serde(rename = "size")]
     size: Option<SizeSource>,
     #[serde(rename = "SIZE")]
@@ -59,7 +209,7 @@ struct SizeKeys {
 }
 
 impl SizeKeys {
-    fn resolve(&self) -> Result<(Option<SizeSource>, bool), LayoutError> {
+    pub(super) fn resolve(&self) -> Result<(Option<SizeSource>, bool), LayoutError> {
         match (&self.size, &self.strict_size) {
             (Some(_), Some(_)) => Err(LayoutError::DataValueExportFailed(
                 "Use either 'size' or 'SIZE', not both.".into(),
@@ -72,18 +222,211 @@ impl SizeKeys {
 }
 
 /// Mutually exclusive source enum.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum EntrySource {
     #[serde(rename = "name")]
     Name(String),
     #[serde(rename = "value")]
     Value(ValueSource),
+    #[serde(rename = "bitfield")]
+    Bitfield(IndexMap<String, BitField>),
+    /// Expression over 'sizeof', 'offsetof' and 'address_of', evaluated once the
+    /// block layout is known.
+    #[serde(rename = "computed")]
+    Computed(String),
+    /// Build-time fact such as the timestamp or build number.
+    #[serde(rename = "build")]
+    Build(BuildField),
+    /// Checksum over the entry named by 'over', computed after assembly.
+    #[serde(rename = "checksum")]
+    Checksum(ChecksumSpec),
+    /// Contents of a binary file, relative to the layout file.
+    #[serde(rename = "file")]
+    File(PathBuf),
+}
+
+/// Retrieves a single value by name, from the current struct array row if there is one.
+pub(super) fn retrieve_single_value(
+    name: &str,
+    data_sheet: Option<&DataSheet>,
+    config: &BuildConfig,
+) -> Result<DataValue, LayoutError> {
+    if let Some(row) = config.row {
+        return row.get(name).cloned().ok_or_else(|| {
+            LayoutError::DataValueExportFailed(format!("Column '{}' is missing or empty", name))
+        });
+    }
+    Ok(require_data_sheet(data_sheet, name)?.retrieve_single_value(name)?)
+}
+
+/// Resolves expression identifiers to layout constants, then Excel names.
+pub(super) struct LiteralScope<'a> {
+    pub data_sheet: Option<&'a DataSheet>,
+    pub config: &'a BuildConfig<'a>,
+}
+
+impl expr::Scope for LiteralScope<'_> {
+    fn variable(&mut self, name: &str) -> Result<DataValue, LayoutError> {
+        match self.config.constants.get(name) {
+            Some(constant) => Ok(constant.clone()),
+            None => retrieve_single_value(name, self.data_sheet, self.config),
+        }
+    }
+
+    fn call(&mut self, name: &str, _arg: &str) -> Result<DataValue, LayoutError> {
+        Err(LayoutError::DataValueExportFailed(format!(
+            "Function '{}' is only available in 'computed' fields",
+            name
+        )))
+    }
+}
+
+/// Evaluates a literal string value as an expression over the layout constants and
+/// Excel names; other values are returned unchanged.
+pub(super) fn evaluate_literal(
+    value: &DataValue,
+    data_sheet: Option<&DataSheet>,
+    config: &BuildConfig,
+) -> Result<DataValue, LayoutError> {
+    let DataValue::Str(src) = value else {
+        return Ok(value.clone());
+    };
+    expr::evaluate(src, &mut LiteralScope { data_sheet, config })
+}
+
+/// Returns the datasheet, or an error naming the field that needed it.
+pub(super) fn require_data_sheet<'a>(
+    data_sheet: Option<&'a DataSheet>,
+    name: &str,
+) -> Result<&'a DataSheet, LayoutError> {
+    data_sheet.ok_or_else(|| {
+        LayoutError::MissingDataSheet(format!(
+            "Field '{}' requires a value from the Excel datasheet, but no datasheet was provided. Use -x to specify an Excel file.",
+            name
+        ))
+    })
 }
 
 impl LeafEntry {
-    /// Returns the alignment of the leaf entry.
-    pub fn get_alignment(&self) -> usize {
-        self.scalar_type.size_bytes()
+    /// Returns the alignment of the leaf entry, capped by the block's max_alignment
+    /// unless overridden with 'align'.
+    pub fn get_alignment(&self, max_alignment: Option<usize>) -> Result<usize, LayoutError> {
+        if let Some(align) = self.align {
+            if !align.is_power_of_two() {
+                return Err(LayoutError::DataValueExportFailed(format!(
+                    "Alignment {} is not a power of two",
+                    align
+                )));
+            }
+            return Ok(align);
+        }
+
+        let natural = self.scalar_type.alignment();
+        Ok(max_alignment.map_or(natural, |max| natural.min(max)))
+    }
+
+    /// Converts a literal element to bytes. Strings are expressions unless the
+    /// leaf uses an enum, in which case they are member names.
+    fn literal_to_bytes(
+        &self,
+        value: &DataValue,
+        data_sheet: Option<&DataSheet>,
+        config: &BuildConfig,
+    ) -> Result<Vec<u8>, LayoutError> {
+        if self.enum_name.is_some() {
+            return self.value_to_bytes(value, config);
+        }
+        let value = evaluate_literal(value, data_sheet, config)?;
+        self.value_to_bytes(&value, config)
+    }
+
+    /// Converts a single element to bytes, resolving enum names and scaling first.
+    pub(super) fn value_to_bytes(
+        &self,
+        value: &DataValue,
+        config: &BuildConfig,
+    ) -> Result<Vec<u8>, LayoutError> {
+        let mut value = value.clone();
+        if let Some(enum_name) = &self.enum_name {
+            value = value.resolve_enum(enum_name, config.enums)?;
+        }
+        self.check_range(&value, config)?;
+        if let Some(scaling) = &self.scaling {
+            value = scaling.apply(&value)?;
+        }
+        value.to_bytes(
+            self.scalar_type,
+            config.endianness,
+            config.strict,
+            self.tolerance,
+        )
+    }
+
+    /// Validates a value against the 'min', 'max' and 'allowed' keys.
+    fn check_range(&self, value: &DataValue, config: &BuildConfig) -> Result<(), LayoutError> {
+        if self.min.is_none() && self.max.is_none() && self.allowed.is_none() {
+            return Ok(());
+        }
+
+        let v = value.as_f64().ok_or_else(|| {
+            LayoutError::DataValueExportFailed(format!(
+                "value {} is not numeric and cannot be range checked",
+                value
+            ))
+        })?;
+
+        if let Some(min) = self.min
+            && v < min
+        {
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "value {} is below minimum {}",
+                value, min
+            )));
+        }
+        if let Some(max) = self.max
+            && v > max
+        {
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "value {} is above maximum {}",
+                value, max
+            )));
+        }
+        if let Some(allowed) = &self.allowed {
+            let mut permitted = Vec::with_capacity(allowed.len());
+            for a in allowed {
+                let a = match &self.enum_name {
+                    Some(enum_name) => a.resolve_enum(enum_name, config.enums)?,
+                    None => a.clone(),
+                };
+                permitted.push(a.as_f64());
+            }
+            if !permitted.contains(&Some(v)) {
+                let names: Vec<_> = allowed.iter().map(|a| a.to_string()).collect();
+                return Err(LayoutError::DataValueExportFailed(format!(
+                    "value {} is not one of the allowed values: {}",
+                    value,
+                    names.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of bytes the leaf always emits.
+    pub fn byte_len(&self) -> Result<usize, LayoutError> {
+        let (size, _) = self.size_keys.resolve()?;
+        let elem = self.scalar_type.size_bytes();
+        let count = match size {
+            None => 1,
+            Some(SizeSource::OneD(size)) => size,
+            Some(SizeSource::TwoD([rows, cols])) => rows.saturating_mul(cols),
+        };
+        count
+            .checked_mul(elem)
+            .ok_or(LayoutError::DataValueExportFailed(
+                "Array size overflow".into(),
+            ))
     }
 
     pub fn emit_bytes(
@@ -92,6 +435,30 @@ impl LeafEntry {
         config: &BuildConfig,
     ) -> Result<Vec<u8>, LayoutError> {
         let (size, strict_len) = self.size_keys.resolve()?;
+        if self.encoding.is_some() && !matches!(size, Some(SizeSource::OneD(_))) {
+            return Err(LayoutError::DataValueExportFailed(
+                "'encoding' only applies to 1D string arrays.".to_string(),
+            ));
+        }
+        if self.tolerance.is_some()
+            && !matches!(self.scalar_type, ScalarType::F16 | ScalarType::BF16)
+        {
+            return Err(LayoutError::DataValueExportFailed(
+                "'tolerance' only applies to f16 and bf16.".to_string(),
+            ));
+        }
+        if (self.file_offset.is_some() || self.file_length.is_some())
+            && !matches!(self.source, EntrySource::File(_))
+        {
+            return Err(LayoutError::DataValueExportFailed(
+                "'file_offset' and 'file_length' only apply to 'file' sources.".to_string(),
+            ));
+        }
+        if self.over.is_some() != matches!(self.source, EntrySource::Checksum(_)) {
+            return Err(LayoutError::DataValueExportFailed(
+                "'checksum' and 'over' must be used together.".to_string(),
+            ));
+        }
         match size {
             None => self.emit_bytes_single(data_sheet, config),
             Some(SizeSource::OneD(size)) => {
@@ -110,24 +477,110 @@ impl LeafEntry {
     ) -> Result<Vec<u8>, LayoutError> {
         match &self.source {
             EntrySource::Name(name) => {
-                let Some(data_sheet) = data_sheet else {
-                    return Err(LayoutError::MissingDataSheet(format!(
-                        "Field '{}' requires a value from the Excel datasheet, but no datasheet was provided. Use -x to specify an Excel file.",
-                        name
-                    )));
-                };
-                let value = data_sheet.retrieve_single_value(name)?;
-                value.to_bytes(self.scalar_type, config.endianness, config.strict)
+                let value = retrieve_single_value(name, data_sheet, config)?;
+                self.value_to_bytes(&value, config)
             }
             EntrySource::Value(ValueSource::Single(v)) => {
-                v.to_bytes(self.scalar_type, config.endianness, config.strict)
+                self.literal_to_bytes(v, data_sheet, config)
             }
             EntrySource::Value(_) => Err(LayoutError::DataValueExportFailed(
                 "Single value expected for scalar type.".to_string(),
             )),
+            EntrySource::Bitfield(fields) => {
+                let word = bitfield::pack(fields, self.scalar_type, data_sheet, config)?;
+                DataValue::U64(word).to_bytes(
+                    self.scalar_type,
+                    config.endianness,
+                    config.strict,
+                    None,
+                )
+            }
+            // Placeholders, overwritten once the block is assembled
+            EntrySource::Computed(_) | EntrySource::Checksum(_) => {
+                Ok(vec![0; self.scalar_type.size_bytes()])
+            }
+            EntrySource::File(_) => Err(LayoutError::DataValueExportFailed(
+                "File contents need a 'size' or 'SIZE'.".to_string(),
+            )),
+            EntrySource::Build(field) => self.value_to_bytes(&config.build.value(*field)?, config),
         }
     }
 
+    /// Encodes a string value, checking the element type matches the encoding.
+    /// "hex:" and "base64:" byte literals are decoded as raw bytes instead.
+    fn string_to_bytes(
+        &self,
+        value: &DataValue,
+        config: &BuildConfig,
+    ) -> Result<Vec<u8>, LayoutError> {
+        if let DataValue::Str(text) = value
+            && let Some(bytes) = byte_literal::decode(text)
+        {
+            if !matches!(self.scalar_type, ScalarType::U8) || self.encoding.is_some() {
+                return Err(LayoutError::DataValueExportFailed(
+                    "Byte literals need type u8 and no 'encoding'.".to_string(),
+                ));
+            }
+            return bytes;
+        }
+
+        let encoding = self.encoding.unwrap_or_default();
+        match self.scalar_type {
+            ScalarType::U16 if encoding.is_utf16() => {}
+            ScalarType::U8 if !encoding.is_utf16() => {}
+            _ if encoding.is_utf16() => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "UTF-16 strings should have type u16.".to_string(),
+                ));
+            }
+            _ => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "Strings should have type u8.".to_string(),
+                ));
+            }
+        }
+        value.string_to_bytes(encoding, config.endianness)
+    }
+
+    /// Errors if an 'encoding' was given for data that is not a string.
+    fn reject_encoding(&self, what: &str) -> Result<(), LayoutError> {
+        match self.encoding {
+            Some(_) => Err(LayoutError::DataValueExportFailed(format!(
+                "'encoding' only applies to strings, not {}.",
+                what
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads a 'file' source, limited to the 'file_offset'/'file_length' range.
+    fn file_bytes(&self, path: &Path, config: &BuildConfig) -> Result<Vec<u8>, LayoutError> {
+        if !matches!(self.scalar_type, ScalarType::U8) {
+            return Err(LayoutError::DataValueExportFailed(
+                "File contents should have type u8.".to_string(),
+            ));
+        }
+        let path = config.base_dir.join(path);
+        let bytes = std::fs::read(&path).map_err(|_| {
+            LayoutError::FileError(format!("failed to open file: {}", path.display()))
+        })?;
+
+        let start = self.file_offset.unwrap_or(0);
+        let end = match self.file_length {
+            Some(len) => start.saturating_add(len),
+            None => bytes.len().max(start),
+        };
+        bytes.get(start..end).map(<[u8]>::to_vec).ok_or_else(|| {
+            LayoutError::DataValueExportFailed(format!(
+                "Byte range {}..{} is outside {} ({} bytes)",
+                start,
+                end,
+                path.display(),
+                bytes.len()
+            ))
+        })
+    }
+
     fn emit_bytes_1d(
         &self,
         data_sheet: Option<&DataSheet>,
@@ -145,51 +598,82 @@ impl LeafEntry {
 
         match &self.source {
             EntrySource::Name(name) => {
-                let Some(data_sheet) = data_sheet else {
-                    return Err(LayoutError::MissingDataSheet(format!(
-                        "Field '{}' requires a value from the Excel datasheet, but no datasheet was provided. Use -x to specify an Excel file.",
-                        name
-                    )));
+                let source = match config.row {
+                    Some(_) => {
+                        ValueSource::Single(retrieve_single_value(name, data_sheet, config)?)
+                    }
+                    None => {
+                        require_data_sheet(data_sheet, name)?.retrieve_1d_array_or_string(name)?
+                    }
                 };
-                match data_sheet.retrieve_1d_array_or_string(name)? {
+                match source {
                     ValueSource::Single(v) => {
-                        if !matches!(self.scalar_type, ScalarType::U8) {
-                            return Err(LayoutError::DataValueExportFailed(
-                                "Strings should have type u8.".to_string(),
-                            ));
-                        }
-                        out.extend(v.string_to_bytes()?);
+                        out.extend(self.string_to_bytes(&v, config)?);
                     }
                     ValueSource::Array(v) => {
+                        self.reject_encoding("numeric arrays")?;
                         for v in v {
-                            out.extend(v.to_bytes(
-                                self.scalar_type,
-                                config.endianness,
-                                config.strict,
-                            )?);
+                            out.extend(self.value_to_bytes(&v, config)?);
                         }
                     }
+                    ValueSource::Matrix(_) => {
+                        return Err(LayoutError::DataValueExportFailed(
+                            "1D array expected, found a list of rows.".to_string(),
+                        ));
+                    }
                 }
             }
             EntrySource::Value(ValueSource::Array(v)) => {
+                self.reject_encoding("numeric arrays")?;
                 for v in v {
-                    out.extend(v.to_bytes(self.scalar_type, config.endianness, config.strict)?);
+                    out.extend(self.literal_to_bytes(v, data_sheet, config)?);
                 }
             }
             EntrySource::Value(ValueSource::Single(v)) => {
-                if !matches!(self.scalar_type, ScalarType::U8) {
+                out.extend(self.string_to_bytes(v, config)?);
+            }
+            EntrySource::Value(ValueSource::Matrix(_)) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "1D array expected, found a list of rows.".to_string(),
+                ));
+            }
+            EntrySource::Bitfield(_) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "Bitfields cannot be arrays.".to_string(),
+                ));
+            }
+            EntrySource::Computed(_) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "Computed fields cannot be arrays.".to_string(),
+                ));
+            }
+            // Digest placeholder, overwritten once the block is assembled
+            EntrySource::Checksum(_) => {
+                self.reject_encoding("checksums")?;
+                out.resize(total_bytes, 0);
+            }
+            EntrySource::File(path) => {
+                self.reject_encoding("file contents")?;
+                out.extend(self.file_bytes(path, config)?);
+            }
+            EntrySource::Build(field) => match config.build.value(*field)? {
+                value @ DataValue::Str(_) => out.extend(self.string_to_bytes(&value, config)?),
+                _ => {
                     return Err(LayoutError::DataValueExportFailed(
-                        "Strings should have type u8.".to_string(),
+                        "Numeric build fields cannot be arrays.".to_string(),
                     ));
                 }
-                out.extend(v.string_to_bytes()?);
-            }
+            },
         }
 
         if out.len() > total_bytes {
-            return Err(LayoutError::DataValueExportFailed(
-                "Array/string is larger than defined size.".to_string(),
-            ));
+            let msg = match self.encoding {
+                Some(StringEncoding::Cstring) => {
+                    "String and its NUL terminator are larger than defined size."
+                }
+                _ => "Array/string is larger than defined size.",
+            };
+            return Err(LayoutError::DataValueExportFailed(msg.to_string()));
         }
         if strict_len && out.len() < total_bytes {
             return Err(LayoutError::DataValueExportFailed(
@@ -209,71 +693,90 @@ impl LeafEntry {
         config: &BuildConfig,
         strict_len: bool,
     ) -> Result<Vec<u8>, LayoutError> {
-        match &self.source {
+        let retrieved;
+        let data: &[Vec<DataValue>] = match &self.source {
             EntrySource::Name(name) => {
-                let Some(data_sheet) = data_sheet else {
-                    return Err(LayoutError::MissingDataSheet(format!(
-                        "Field '{}' requires a value from the Excel datasheet, but no datasheet was provided. Use -x to specify an Excel file.",
-                        name
-                    )));
-                };
-                let data = data_sheet.retrieve_2d_array(name)?;
-
-                let rows = size[0];
-                let cols = size[1];
-
-                let elem = self.scalar_type.size_bytes();
-                let total_elems =
-                    rows.checked_mul(cols)
-                        .ok_or(LayoutError::DataValueExportFailed(
-                            "2D size overflow".into(),
-                        ))?;
-                let total_bytes =
-                    total_elems
-                        .checked_mul(elem)
-                        .ok_or(LayoutError::DataValueExportFailed(
-                            "2D byte count overflow".into(),
-                        ))?;
-
-                if data.iter().any(|row| row.len() != cols) {
+                if config.row.is_some() {
                     return Err(LayoutError::DataValueExportFailed(
-                        "2D array column count mismatch.".to_string(),
+                        "2D arrays cannot be read from struct array rows.".to_string(),
                     ));
                 }
+                let data_sheet = require_data_sheet(data_sheet, name)?;
+                retrieved = data_sheet.retrieve_2d_array(name)?;
+                &retrieved
+            }
+            EntrySource::Value(ValueSource::Matrix(rows)) => rows,
+            EntrySource::Value(_) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "2D array expected as a list of rows.".to_string(),
+                ));
+            }
+            EntrySource::Bitfield(_) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "Bitfields cannot be arrays.".to_string(),
+                ));
+            }
+            EntrySource::Computed(_)
+            | EntrySource::Build(_)
+            | EntrySource::Checksum(_)
+            | EntrySource::File(_) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "2D arrays must come from a name or a list of rows.".to_string(),
+                ));
+            }
+        };
 
-                if data.len() > rows {
-                    return Err(LayoutError::DataValueExportFailed(
-                        "2D array row count greater than defined size.".to_string(),
-                    ));
-                }
+        let rows = size[0];
+        let cols = size[1];
 
-                if strict_len && data.len() < rows {
-                    return Err(LayoutError::DataValueExportFailed(
-                        "2D array row count smaller than defined size (strict SIZE).".to_string(),
-                    ));
-                }
+        let elem = self.scalar_type.size_bytes();
+        let total_elems = rows
+            .checked_mul(cols)
+            .ok_or(LayoutError::DataValueExportFailed(
+                "2D size overflow".into(),
+            ))?;
+        let total_bytes =
+            total_elems
+                .checked_mul(elem)
+                .ok_or(LayoutError::DataValueExportFailed(
+                    "2D byte count overflow".into(),
+                ))?;
 
-                let mut out = Vec::with_capacity(total_bytes);
-                for row in data {
-                    for v in row {
-                        out.extend(v.to_bytes(
-                            self.scalar_type,
-                            config.endianness,
-                            config.strict,
-                        )?);
-                    }
-                }
+        if data.iter().any(|row| row.len() != cols) {
+            return Err(LayoutError::DataValueExportFailed(
+                "2D array column count mismatch.".to_string(),
+            ));
+        }
 
-                while out.len() < total_bytes {
-                    out.push(config.padding);
-                }
+        if data.len() > rows {
+            return Err(LayoutError::DataValueExportFailed(
+                "2D array row count greater than defined size.".to_string(),
+            ));
+        }
 
-                Ok(out)
+        if strict_len && data.len() < rows {
+            return Err(LayoutError::DataValueExportFailed(
+                "2D array row count smaller than defined size (strict SIZE).".to_string(),
+            ));
+        }
+
+        let literal = matches!(self.source, EntrySource::Value(_));
+        let mut out = Vec::with_capacity(total_bytes);
+        for row in data {
+            for v in row {
+                if literal {
+                    out.extend(self.literal_to_bytes(v, data_sheet, config)?);
+                } else {
+                    out.extend(self.value_to_bytes(v, config)?);
+                }
             }
-            EntrySource::Value(_) => Err(LayoutError::DataValueExportFailed(
-                "2D arrays within the layout file are not supported.".to_string(),
-            )),
         }
+
+        while out.len() < total_bytes {
+            out.push(config.padding);
+        }
+
+        Ok(out)
     }
 }
 
@@ -282,9 +785,19 @@ impl ScalarType {
     pub fn size_bytes(&self) -> usize {
         match self {
             ScalarType::U8 | ScalarType::I8 => 1,
-            ScalarType::U16 | ScalarType::I16 => 2,
+            ScalarType::U16 | ScalarType::I16 | ScalarType::F16 | ScalarType::BF16 => 2,
             ScalarType::U32 | ScalarType::I32 | ScalarType::F32 => 4,
             ScalarType::U64 | ScalarType::I64 | ScalarType::F64 => 8,
+            ScalarType::Fixed(q) => q.bits as usize / 8,
+            ScalarType::OddInt(int) => int.bits as usize / 8,
         }
     }
+
+    /// Returns the natural alignment of the type. Odd-width integers have no C
+    /// equivalent, so they align to the largest power of two dividing their size
+    /// (u24 to 1 byte, u48 to 2 bytes).
+    pub fn alignment(&self) -> usize {
+        let size = self.size_bytes();
+        1 << size.trailing_zeros()
+    }
 }
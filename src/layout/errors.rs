@@ -8,6 +8,12 @@ pub enum LayoutError {
     #[error("Block not found: {0}.")]
     BlockNotFound(String),
 
+    #[error("Type not found: {0}.")]
+    TypeNotFound(String),
+
+    #[error("Recursive type definition: {0}.")]
+    RecursiveType(String),
+
     #[error("Data value export failed: {0}.")]
     DataValueExportFailed(String),
 
@@ -17,6 +23,9 @@ pub enum LayoutError {
     #[error("No blocks provided.")]
     NoBlocksProvided,
 
+    #[error("Build metadata error: {0}.")]
+    BuildInfo(String),
+
     #[error("Missing datasheet: {0}")]
     MissingDataSheet(String),
 
@@ -0,0 +1,425 @@
+use super::errors::LayoutError;
+use super::value::DataValue;
+
+/// Resolves the identifiers and function calls appearing in an expression.
+pub trait Scope {
+    fn variable(&mut self, name: &str) -> Result<DataValue, LayoutError>;
+
+    /// Called for `name(arg)`; the argument is passed as unevaluated, trimmed text.
+    fn call(&mut self, name: &str, arg: &str) -> Result<DataValue, LayoutError>;
+}
+
+/// Evaluates an arithmetic expression such as `"(1 << 12) | 0x3"`.
+///
+/// Supports integer (decimal, 0x, 0o, 0b) and float literals, parentheses, unary
+/// `- + ~` and the binary operators `* / % + - << >> & ^ |` with C precedence.
+/// Identifiers and function calls are resolved through `scope`. Integer arithmetic
+/// is exact and errors on overflow; any float operand makes the result a float.
+pub fn evaluate(src: &str, scope: &mut dyn Scope) -> Result<DataValue, LayoutError> {
+    let mut parser = Parser { src, pos: 0, scope };
+    let result = parser.expression(0).and_then(|value| {
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(value),
+            Some(c) => Err(parser.error(format!("unexpected '{}'", c))),
+        }
+    });
+
+    result.and_then(Num::into_data_value).map_err(|e| match e {
+        ExprError::Syntax(msg) => {
+            LayoutError::DataValueExportFailed(format!("Invalid expression '{}': {}", src, msg))
+        }
+        ExprError::Layout(e) => e,
+    })
+}
+
+enum ExprError {
+    Syntax(String),
+    /// Errors from identifier lookup are passed through unchanged.
+    Layout(LayoutError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Num {
+    Int(i128),
+    Float(f64),
+}
+
+impl Num {
+    fn from_data_value(value: &DataValue, name: &str) -> Result<Self, ExprError> {
+        match value {
+            DataValue::U64(v) => Ok(Num::Int(*v as i128)),
+            DataValue::I64(v) => Ok(Num::Int(*v as i128)),
+            DataValue::F64(v) => Ok(Num::Float(*v)),
+            DataValue::Str(s) => Err(ExprError::Syntax(format!(
+                "'{}' is the string \"{}\", not a number",
+                name, s
+            ))),
+        }
+    }
+
+    fn into_data_value(self) -> Result<DataValue, ExprError> {
+        match self {
+            Num::Float(v) => Ok(DataValue::F64(v)),
+            Num::Int(v) if v >= 0 => u64::try_from(v)
+                .map(DataValue::U64)
+                .map_err(|_| ExprError::Syntax(format!("result {} does not fit in 64 bits", v))),
+            Num::Int(v) => i64::try_from(v)
+                .map(DataValue::I64)
+                .map_err(|_| ExprError::Syntax(format!("result {} does not fit in 64 bits", v))),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Num::Int(v) => v as f64,
+            Num::Float(v) => v,
+        }
+    }
+}
+
+/// Binary operators with their C precedence; higher binds tighter.
+const BINARY_OPS: [(&str, u8); 10] = [
+    ("<<", 5),
+    (">>", 5),
+    ("*", 7),
+    ("/", 7),
+    ("%", 7),
+    ("+", 6),
+    ("-", 6),
+    ("&", 4),
+    ("^", 3),
+    ("|", 2),
+];
+
+struct Parser<'a, 's> {
+    src: &'a str,
+    pos: usize,
+    scope: &'s mut dyn Scope,
+}
+
+impl<'a> Parser<'a, '_> {
+    fn error(&self, msg: String) -> ExprError {
+        ExprError::Syntax(format!("{} at position {}", msg, self.pos))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek()
+            && c.is_whitespace()
+        {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Precedence climbing over the binary operators.
+    fn expression(&mut self, min_precedence: u8) -> Result<Num, ExprError> {
+        let mut lhs = self.unary()?;
+        loop {
+            self.skip_whitespace();
+            let rest = &self.src[self.pos..];
+            let Some((op, precedence)) = BINARY_OPS
+                .iter()
+                .find(|(op, _)| rest.starts_with(op))
+                .copied()
+            else {
+                return Ok(lhs);
+            };
+            if precedence <= min_precedence {
+                return Ok(lhs);
+            }
+            self.pos += op.len();
+            let rhs = self.expression(precedence)?;
+            lhs = self.binary(op, lhs, rhs)?;
+        }
+    }
+
+    fn unary(&mut self) -> Result<Num, ExprError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                match self.unary()? {
+                    Num::Int(v) => v
+                        .checked_neg()
+                        .map(Num::Int)
+                        .ok_or_else(|| self.error("overflow in '-'".to_string())),
+                    Num::Float(v) => Ok(Num::Float(-v)),
+                }
+            }
+            Some('+') => {
+                self.pos += 1;
+                self.unary()
+            }
+            Some('~') => {
+                self.pos += 1;
+                match self.unary()? {
+                    Num::Int(v) => Ok(Num::Int(!v)),
+                    Num::Float(_) => Err(self.error("'~' requires an integer".to_string())),
+                }
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Num, ExprError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let value = self.expression(0)?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    return Err(self.error("expected ')'".to_string()));
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.identifier();
+                self.skip_whitespace();
+                let value = if self.peek() == Some('(') {
+                    let arg = self.call_argument()?;
+                    self.scope.call(name, arg)
+                } else {
+                    self.scope.variable(name)
+                };
+                Num::from_data_value(&value.map_err(ExprError::Layout)?, name)
+            }
+            Some(c) => Err(self.error(format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end of expression".to_string())),
+        }
+    }
+
+    fn identifier(&mut self) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek()
+            && (c.is_alphanumeric() || c == '_' || c == '.')
+        {
+            self.pos += c.len_utf8();
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Consumes a parenthesised argument and returns its text without the parentheses.
+    fn call_argument(&mut self) -> Result<&'a str, ExprError> {
+        let start = self.pos + 1;
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 1 => return Ok(self.src[start..self.pos - 1].trim()),
+                ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        Err(self.error("expected ')'".to_string()))
+    }
+
+    fn number(&mut self) -> Result<Num, ExprError> {
+        let start = self.pos;
+        let rest = &self.src[start..];
+        let radix = match rest.get(..2).map(|p| p.to_ascii_lowercase()) {
+            Some(p) if p == "0x" => 16,
+            Some(p) if p == "0o" => 8,
+            Some(p) if p == "0b" => 2,
+            _ => 10,
+        };
+
+        if radix != 10 {
+            self.pos += 2;
+            let digits_start = self.pos;
+            while let Some(c) = self.peek()
+                && (c.is_digit(radix) || c == '_')
+            {
+                self.pos += 1;
+            }
+            let digits = self.src[digits_start..self.pos].replace('_', "");
+            return i128::from_str_radix(&digits, radix)
+                .map(Num::Int)
+                .map_err(|_| {
+                    self.error(format!("invalid number '{}'", &self.src[start..self.pos]))
+                });
+        }
+
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || c == '_' {
+                self.pos += 1;
+            } else if c == '.' {
+                is_float = true;
+                self.pos += 1;
+            } else if c == 'e' || c == 'E' {
+                is_float = true;
+                self.pos += 1;
+                if matches!(self.peek(), Some('+' | '-')) {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+
+        let text = self.src[start..self.pos].replace('_', "");
+        let parsed = if is_float {
+            text.parse::<f64>().map(Num::Float).ok()
+        } else {
+            text.parse::<i128>().map(Num::Int).ok()
+        };
+        parsed.ok_or_else(|| self.error(format!("invalid number '{}'", text)))
+    }
+
+    fn binary(&self, op: &str, lhs: Num, rhs: Num) -> Result<Num, ExprError> {
+        let overflow = || self.error(format!("overflow in '{}'", op));
+
+        let (Num::Int(a), Num::Int(b)) = (lhs, rhs) else {
+            let (a, b) = (lhs.as_f64(), rhs.as_f64());
+            return match op {
+                "+" => Ok(Num::Float(a + b)),
+                "-" => Ok(Num::Float(a - b)),
+                "*" => Ok(Num::Float(a * b)),
+                "/" => Ok(Num::Float(a / b)),
+                "%" => Ok(Num::Float(a % b)),
+                _ => Err(self.error(format!("'{}' requires integer operands", op))),
+            };
+        };
+
+        let value = match op {
+            "+" => a.checked_add(b).ok_or_else(overflow)?,
+            "-" => a.checked_sub(b).ok_or_else(overflow)?,
+            "*" => a.checked_mul(b).ok_or_else(overflow)?,
+            "/" | "%" if b == 0 => return Err(self.error("division by zero".to_string())),
+            "/" => a.checked_div(b).ok_or_else(overflow)?,
+            "%" => a.checked_rem(b).ok_or_else(overflow)?,
+            "<<" | ">>" if !(0..64).contains(&b) => {
+                return Err(self.error(format!("shift amount {} out of range", b)));
+            }
+            // Shifting out significant bits counts as overflow
+            "<<" if (a << b) >> b != a => return Err(overflow()),
+            "<<" => a << b,
+            ">>" => a >> b,
+            "&" => a & b,
+            "^" => a ^ b,
+            "|" => a | b,
+            _ => unreachable!("operator table and evaluator out of sync"),
+        };
+        Ok(Num::Int(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestScope;
+
+    impl Scope for TestScope {
+        fn variable(&mut self, name: &str) -> Result<DataValue, LayoutError> {
+            match name {
+                "FW_MAJOR" => Ok(DataValue::U64(2)),
+                "FW_MINOR" => Ok(DataValue::U64(7)),
+                "GAIN" => Ok(DataValue::F64(1.5)),
+                _ => Err(LayoutError::DataValueExportFailed(format!(
+                    "unknown name {}",
+                    name
+                ))),
+            }
+        }
+
+        fn call(&mut self, name: &str, arg: &str) -> Result<DataValue, LayoutError> {
+            match name {
+                "len" => Ok(DataValue::U64(arg.len() as u64)),
+                _ => Err(LayoutError::DataValueExportFailed(format!(
+                    "unknown function {}",
+                    name
+                ))),
+            }
+        }
+    }
+
+    fn eval(src: &str) -> Result<DataValue, LayoutError> {
+        evaluate(src, &mut TestScope)
+    }
+
+    fn int(src: &str) -> i128 {
+        match eval(src).expect("expression evaluates") {
+            DataValue::U64(v) => v as i128,
+            DataValue::I64(v) => v as i128,
+            other => panic!("expected integer, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn integer_operators_follow_c_precedence() {
+        assert_eq!(int("(1 << 12) | 0x3"), 0x1003);
+        assert_eq!(int("1 + 2 * 3"), 7);
+        assert_eq!(int("1 << 2 + 1"), 8);
+        assert_eq!(int("0xF0 & 0x3C ^ 0x01 | 0b100"), 0x35);
+        assert_eq!(int("-7 / 2"), -3);
+        assert_eq!(int("-7 % 2"), -1);
+        assert_eq!(int("~0 & 0xFF"), 0xFF);
+        assert_eq!(int("10 - 2 - 3"), 5);
+        assert_eq!(int("1_000 * 0o10"), 8000);
+    }
+
+    #[test]
+    fn identifiers_and_floats() {
+        assert_eq!(int("FW_MAJOR * 256 + FW_MINOR"), 0x0207);
+        assert!(matches!(eval("GAIN * 2"), Ok(DataValue::F64(v)) if v == 3.0));
+        assert!(matches!(eval("1 / 4.0"), Ok(DataValue::F64(v)) if v == 0.25));
+        assert!(matches!(eval("1e3"), Ok(DataValue::F64(v)) if v == 1000.0));
+    }
+
+    #[test]
+    fn function_arguments_are_passed_as_text() {
+        assert_eq!(int("len(a.b[2]) * 2"), 12);
+        assert_eq!(int("len( (x) ) + 1"), 4);
+        assert_eq!(int("len()"), 0);
+        assert!(eval("len(a").is_err());
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        for src in [
+            "1 +",
+            "(1 + 2",
+            "1 2",
+            "1 / 0",
+            "GAIN << 1",
+            "1 << 64",
+            "0x",
+            "0xFFFFFFFFFFFFFFFF + 1",
+        ] {
+            let err = eval(src).expect_err(src).to_string();
+            assert!(err.contains("Invalid expression"), "{}: {}", src, err);
+        }
+        assert!(
+            eval("MISSING + 1")
+                .unwrap_err()
+                .to_string()
+                .contains("unknown name")
+        );
+    }
+
+    #[test]
+    fn intermediate_overflow_is_reported() {
+        for src in [
+            "-(1 << 63) * (1 << 63) * 2 / -1",
+            "-(1 << 63) * (1 << 63) * 2 % -1",
+            "-(-(1 << 63) * (1 << 63) * 2)",
+            "(1 << 63) * (1 << 63) << 10",
+            "1 << 63 << 63 << 2",
+        ] {
+            let err = eval(src).expect_err(src).to_string();
+            assert!(err.contains("overflow in"), "{}: {}", src, err);
+        }
+        // Shifts that keep every bit stay exact
+        assert_eq!(int("(1 << 63) * (1 << 63) >> 63 >> 40"), 1 << 23);
+        assert_eq!(int("-1 << 63"), i64::MIN as i128);
+    }
+}
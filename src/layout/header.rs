@@ -7,6 +7,9 @@ pub struct Header {
     pub crc_location: CrcLocation,
     #[serde(default = "default_padding")]
     pub padding: u8,
+    /// Caps the natural alignment of fields, e.g. 1 for packed structs.
+    #[serde(default)]
+    pub max_alignment: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
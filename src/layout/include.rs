@@ -0,0 +1,194 @@
+use super::errors::LayoutError;
+use super::parse_file;
+
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Sections whose entries are merged by name across included files.
+const NAMED_SECTIONS: [&str; 3] = ["constants", "enums", "types"];
+
+/// Layout document assembled from a file and everything it includes.
+#[derive(Default)]
+struct Merged {
+    doc: Map<String, Value>,
+    /// File each block, enum and type was first defined in, for duplicate errors.
+    origins: HashMap<String, PathBuf>,
+    /// Directory of the root layout file, which relative paths resolve against.
+    root_dir: PathBuf,
+    /// Every file loaded, root first.
+    files: Vec<PathBuf>,
+}
+
+/// Loads a layout file and recursively merges the files listed in its 'include' key.
+/// Included files are merged first, so settings in the including file take precedence.
+/// Also returns the paths of all files read.
+pub(super) fn resolve(path: &Path) -> Result<(Value, Vec<PathBuf>), LayoutError> {
+    let mut merged = Merged {
+        root_dir: path.parent().unwrap_or(Path::new("")).to_path_buf(),
+        ..Merged::default()
+    };
+    load(path, &mut Vec::new(), &mut HashSet::new(), &mut merged)?;
+    Ok((Value::Object(merged.doc), merged.files))
+}
+
+fn load(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    visited: &mut HashSet<PathBuf>,
+    merged: &mut Merged,
+) -> Result<(), LayoutError> {
+    let canonical = std::fs::canonicalize(path)
+        .map_err(|_| LayoutError::FileError(format!("failed to open file: {}", path.display())))?;
+
+    if let Some(pos) = stack.iter().position(|p| *p == canonical) {
+        let cycle: Vec<String> = stack[pos..]
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(LayoutError::FileError(format!(
+            "include cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+    // A file reached through several include paths is only merged once.
+    if visited.contains(&canonical) {
+        return Ok(());
+    }
+    merged.files.push(path.to_path_buf());
+
+    let Value::Object(mut doc) = parse_file::<Value>(path)? else {
+        return Err(LayoutError::FileError(format!(
+            "top level of {} must be a table",
+            path.display()
+        )));
+    };
+
+    let includes = match doc.shift_remove("include") {
+        None => Vec::new(),
+        Some(Value::Array(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(s) => Ok(s),
+                other => Err(LayoutError::FileError(format!(
+                    "include entries in {} must be file paths, found {}",
+                    path.display(),
+                    other
+                ))),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(other) => {
+            return Err(LayoutError::FileError(format!(
+                "include in {} must be a list of file paths, found {}",
+                path.display(),
+                other
+            )));
+        }
+    };
+
+    // The root file's paths already resolve against the layout directory
+    if !stack.is_empty() {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let dir = dir.strip_prefix(&merged.root_dir).unwrap_or(dir);
+        for (key, value) in doc.iter_mut() {
+            if !matches!(key.as_str(), "constants" | "enums") {
+                anchor_paths(value, dir);
+            }
+        }
+    }
+
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    stack.push(canonical.clone());
+    for include in includes {
+        load(&base_dir.join(include), stack, visited, merged)?;
+    }
+    stack.pop();
+    visited.insert(canonical);
+
+    merge(doc, path, merged)
+}
+
+fn merge(doc: Map<String, Value>, path: &Path, merged: &mut Merged) -> Result<(), LayoutError> {
+    for (key, value) in doc {
+        if key == "settings" {
+            let settings = merged.doc.entry(key).or_insert(Value::Null);
+            deep_merge(settings, value);
+        } else if NAMED_SECTIONS.contains(&key.as_str()) {
+            let Value::Object(items) = value else {
+                return Err(LayoutError::FileError(format!(
+                    "'{}' in {} must be a table",
+                    key,
+                    path.display()
+                )));
+            };
+            let section = merged
+                .doc
+                .entry(key.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(section) = section {
+                for (name, item) in items {
+                    claim(&mut merged.origins, &key, &name, path)?;
+                    section.insert(name, item);
+                }
+            }
+        } else {
+            claim(&mut merged.origins, "block", &key, path)?;
+            merged.doc.insert(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Prefixes the paths of 'file' sources and digest 'key_file's with the included
+/// file's directory relative to the root layout, so that they resolve against the
+/// directory of the file that declares them.
+fn anchor_paths(value: &mut Value, dir: &Path) {
+    match value {
+        Value::Object(map) => {
+            let is_leaf = map.contains_key("type");
+            for (key, item) in map.iter_mut() {
+                match item {
+                    Value::String(file) if key == "key_file" || (is_leaf && key == "file") => {
+                        *file = dir.join(&*file).to_string_lossy().into_owned();
+                    }
+                    _ => anchor_paths(item, dir),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| anchor_paths(item, dir)),
+        _ => {}
+    }
+}
+
+/// Records where a definition came from, failing if another file already defined it.
+fn claim(
+    origins: &mut HashMap<String, PathBuf>,
+    kind: &str,
+    name: &str,
+    path: &Path,
+) -> Result<(), LayoutError> {
+    if let Some(previous) = origins.get(&format!("{}.{}", kind, name)) {
+        return Err(LayoutError::FileError(format!(
+            "duplicate {} '{}' defined in both {} and {}",
+            kind.trim_end_matches('s'),
+            name,
+            previous.display(),
+            path.display()
+        )));
+    }
+    origins.insert(format!("{}.{}", kind, name), path.to_path_buf());
+    Ok(())
+}
+
+/// Merges tables key by key; any other value in 'overlay' replaces the base value.
+fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                deep_merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
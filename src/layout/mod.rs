@@ -1,42 +1,86 @@
 pub mod args;
+mod bitfield;
 pub mod block;
+pub mod build_info;
+mod byte_literal;
+mod checksum;
 mod conversions;
+pub mod crc_catalogue;
 mod entry;
 pub mod errors;
+mod expr;
 pub mod header;
+mod include;
+mod scaling;
 pub mod settings;
+mod struct_array;
+mod types;
 pub mod value;
 
 use block::Config;
 use errors::LayoutError;
+use serde::de::DeserializeOwned;
 use std::path::Path;
 
 pub fn load_layout(filename: &str) -> Result<Config, LayoutError> {
-    let text = std::fs::read_to_string(filename)
+    let path = Path::new(filename);
+
+    // Layouts without includes are parsed directly so errors keep their file positions.
+    let root: serde_json::Value = parse_file(path)?;
+    let mut cfg: Config = if root.get("include").is_some() {
+        let (doc, sources) = include::resolve(path)?;
+        let mut cfg: Config = serde_json::from_value(doc).map_err(|e| {
+            LayoutError::FileError(format!("failed to parse file {}: {}", filename, e))
+        })?;
+        cfg.sources = sources;
+        cfg
+    } else {
+        let mut cfg: Config = parse_file(path)?;
+        cfg.sources = vec![path.to_path_buf()];
+        cfg
+    };
+
+    cfg.base_dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    cfg.expand_types()?;
+
+    // Block overrides can narrow the width below the inherited polynomial
+    for (name, block) in &cfg.blocks {
+        block
+            .effective_settings(&cfg)
+            .crc
+            .check_width()
+            .map_err(|e| LayoutError::InField {
+                field: name.clone(),
+                source: Box::new(LayoutError::InvalidBlockArgument(e)),
+            })?;
+    }
+
+    Ok(cfg)
+}
+
+fn parse_file<T: DeserializeOwned>(path: &Path) -> Result<T, LayoutError> {
+    let filename = path.display();
+    let text = std::fs::read_to_string(path)
         .map_err(|_| LayoutError::FileError(format!("failed to open file: {}", filename)))?;
 
-    let ext = Path::new(filename)
+    let ext = path
         .extension()
         .and_then(|s| s.to_str())
         .map(|s| s.to_ascii_lowercase())
         .unwrap_or_default();
 
-    let cfg: Config = match ext.as_str() {
+    match ext.as_str() {
         "toml" => toml::from_str(&text).map_err(|e| {
             LayoutError::FileError(format!("failed to parse file {}: {}", filename, e))
-        })?,
+        }),
         "yaml" | "yml" => serde_yaml::from_str(&text).map_err(|e| {
             LayoutError::FileError(format!("failed to parse file {}: {}", filename, e))
-        })?,
+        }),
         "json" => serde_json::from_str(&text).map_err(|e| {
             LayoutError::FileError(format!("failed to parse file {}: {}", filename, e))
-        })?,
-        _ => {
-            return Err(LayoutError::FileError(
-                "Unsupported file format".to_string(),
-            ));
-        }
-    };
-
-    Ok(cfg)
+        }),
+        _ => Err(LayoutError::FileError(
+            "Unsupported file format".to_string(),
+        )),
+    }
 }
@@ -0,0 +1,59 @@
+use super::errors::LayoutError;
+use super::value::DataValue;
+use serde::Deserialize;
+
+/// Physical-to-raw conversion applied to leaf values: raw = (phys - offset) / scale.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scaling {
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+    #[serde(default)]
+    pub rounding: Rounding,
+}
+
+/// Rounding applied to the raw value. With 'none' the unrounded value is passed on,
+/// so strict mode rejects inexact results for integer types.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    #[default]
+    None,
+    Nearest,
+    Floor,
+    Ceil,
+    Truncate,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl Scaling {
+    /// Converts a physical value into its raw representation.
+    pub fn apply(&self, value: &DataValue) -> Result<DataValue, LayoutError> {
+        let phys = value.as_f64().ok_or(LayoutError::DataValueExportFailed(
+            "Cannot scale a string value.".to_string(),
+        ))?;
+
+        if self.scale == 0.0 || !self.scale.is_finite() {
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "Invalid scale factor {}",
+                self.scale
+            )));
+        }
+
+        let raw = (phys - self.offset) / self.scale;
+        let raw = match self.rounding {
+            Rounding::None => raw,
+            Rounding::Nearest => raw.round(),
+            Rounding::Floor => raw.floor(),
+            Rounding::Ceil => raw.ceil(),
+            Rounding::Truncate => raw.trunc(),
+        };
+
+        Ok(DataValue::F64(raw))
+    }
+}
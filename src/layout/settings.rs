@@ -1,6 +1,12 @@
-use serde::Deserialize;
+use super::crc_catalogue;
+use super::errors::LayoutError;
 
-#[derive(Debug, Deserialize)]
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
     pub endianness: Endianness,
     #[serde(default = "default_offset")]
@@ -10,6 +16,9 @@ pub struct Settings {
     #[serde(default)]
     pub pad_to_end: bool,
     pub crc: CrcData,
+    /// Digest stored in place of the CRC at the block's 'crc_location'.
+    #[serde(default)]
+    pub digest: Option<DigestData>,
 }
 
 #[derive(Debug, Deserialize, Clone, Copy)]
@@ -19,24 +28,373 @@ pub enum Endianness {
     Big,
 }
 
-#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum CrcArea {
+    #[default]
     #[serde(rename = "data")]
     Data,
     #[serde(rename = "block")]
     Block,
 }
 
-#[derive(Debug, Deserialize)]
+/// CRC register width in bits; one of 8, 16, 32 or 64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "u32")]
+pub struct CrcWidth(u32);
+
+impl CrcWidth {
+    pub const W8: CrcWidth = CrcWidth(8);
+    pub const W16: CrcWidth = CrcWidth(16);
+    pub const W32: CrcWidth = CrcWidth(32);
+    pub const W64: CrcWidth = CrcWidth(64);
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn bytes(self) -> usize {
+        self.0 as usize / 8
+    }
+
+    /// Mask of the bits that fit in the register.
+    pub const fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.0)
+    }
+}
+
+impl Default for CrcWidth {
+    fn default() -> Self {
+        CrcWidth::W32
+    }
+}
+
+impl TryFrom<u32> for CrcWidth {
+    type Error = String;
+
+    fn try_from(bits: u32) -> Result<Self, Self::Error> {
+        match bits {
+            8 | 16 | 32 | 64 => Ok(CrcWidth(bits)),
+            _ => Err(format!("CRC width must be 8, 16, 32 or 64, found {}", bits)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "CrcSpec")]
 pub struct CrcData {
-    pub polynomial: u32,
-    pub start: u32,
-    pub xor_out: u32,
+    /// Register width; 32 bits unless set.
+    pub width: CrcWidth,
+    pub polynomial: u64,
+    pub start: u64,
+    pub xor_out: u64,
     pub ref_in: bool,
     pub ref_out: bool,
     pub area: CrcArea,
 }
 
+impl CrcData {
+    /// Checks that the polynomial, start and xor_out values fit in the register.
+    pub fn check_width(&self) -> Result<(), String> {
+        for (key, value) in [
+            ("polynomial", self.polynomial),
+            ("start", self.start),
+            ("xor_out", self.xor_out),
+        ] {
+            if value & !self.width.mask() != 0 {
+                return Err(format!(
+                    "CRC {} 0x{:X} does not fit in {} bits",
+                    key,
+                    value,
+                    self.width.bits()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// CRC settings as written in a layout: a catalogue name such as "CRC-16/XMODEM",
+/// or a table of parameters that may start from a 'preset'.
+enum CrcSpec {
+    Preset(String),
+    Table(CrcTable),
+}
+
+// Written out rather than untagged so errors inside the table keep their message.
+impl<'de> Deserialize<'de> for CrcSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SpecVisitor;
+
+        impl<'de> Visitor<'de> for SpecVisitor {
+            type Value = CrcSpec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a CRC preset name or a table of CRC parameters")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<CrcSpec, E> {
+                Ok(CrcSpec::Preset(name.to_string()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<CrcSpec, A::Error> {
+                CrcTable::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(CrcSpec::Table)
+            }
+        }
+
+        deserializer.deserialize_any(SpecVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CrcTable {
+    preset: Option<String>,
+    width: Option<CrcWidth>,
+    polynomial: Option<u64>,
+    start: Option<u64>,
+    xor_out: Option<u64>,
+    ref_in: Option<bool>,
+    ref_out: Option<bool>,
+    area: Option<CrcArea>,
+}
+
+impl TryFrom<CrcSpec> for CrcData {
+    type Error = String;
+
+    fn try_from(spec: CrcSpec) -> Result<Self, Self::Error> {
+        spec.into_crc(None)
+    }
+}
+
+impl CrcSpec {
+    /// Expands the spec; 'area' falls back to the preset, then to `default_area`.
+    fn into_crc(self, default_area: Option<CrcArea>) -> Result<CrcData, String> {
+        let table = match self {
+            CrcSpec::Preset(name) => return Ok(preset(&name)?.clone()),
+            CrcSpec::Table(table) => table,
+        };
+        let base = table.preset.as_deref().map(preset).transpose()?;
+        let missing =
+            |key: &str| format!("CRC settings need '{}' or a 'preset' to take it from", key);
+
+        let crc = CrcData {
+            width: table.width.or(base.map(|b| b.width)).unwrap_or_default(),
+            polynomial: table
+                .polynomial
+                .or(base.map(|b| b.polynomial))
+                .ok_or_else(|| missing("polynomial"))?,
+            start: table
+                .start
+                .or(base.map(|b| b.start))
+                .ok_or_else(|| missing("start"))?,
+            xor_out: table
+                .xor_out
+                .or(base.map(|b| b.xor_out))
+                .ok_or_else(|| missing("xor_out"))?,
+            ref_in: table
+                .ref_in
+                .or(base.map(|b| b.ref_in))
+                .ok_or_else(|| missing("ref_in"))?,
+            ref_out: table
+                .ref_out
+                .or(base.map(|b| b.ref_out))
+                .ok_or_else(|| missing("ref_out"))?,
+            area: table
+                .area
+                .or(base.map(|b| b.area))
+                .or(default_area)
+                .ok_or_else(|| missing("area"))?,
+        };
+        crc.check_width()?;
+        Ok(crc)
+    }
+}
+
+/// CRC parameters of a checksum leaf, where 'area' has no meaning and may be left out.
+pub fn deserialize_leaf_crc<'de, D>(deserializer: D) -> Result<CrcData, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    CrcSpec::deserialize(deserializer)?
+        .into_crc(Some(CrcArea::Data))
+        .map_err(de::Error::custom)
+}
+
+/// Parameters of a catalogued CRC algorithm.
+fn preset(name: &str) -> Result<&'static CrcData, String> {
+    crc_catalogue::find(name)
+        .map(|entry| &entry.crc)
+        .ok_or_else(|| {
+            format!(
+                "unknown CRC preset '{}'; expected one of {}",
+                name,
+                crc_catalogue::names().join(", ")
+            )
+        })
+}
+
+fn deserialize_preset<'de, D>(deserializer: D) -> Result<Option<&'static CrcData>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let name = String::deserialize(deserializer)?;
+    preset(&name).map(Some).map_err(de::Error::custom)
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    #[serde(rename = "sha256")]
+    Sha256,
+    #[serde(rename = "hmac-sha256")]
+    HmacSha256,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DigestData {
+    pub algorithm: DigestAlgorithm,
+    /// File holding the raw HMAC key, relative to the layout file that declares it.
+    pub key_file: Option<PathBuf>,
+    #[serde(default)]
+    pub area: CrcArea,
+}
+
+impl DigestData {
+    /// Returns a copy with the key file resolved against the given directory.
+    pub fn relative_to(&self, base_dir: &Path) -> DigestData {
+        DigestData {
+            key_file: self.key_file.as_ref().map(|f| base_dir.join(f)),
+            ..self.clone()
+        }
+    }
+
+    /// Reads the HMAC key; plain SHA-256 has no key.
+    pub fn key(&self) -> Result<Vec<u8>, LayoutError> {
+        match (self.algorithm, &self.key_file) {
+            (DigestAlgorithm::Sha256, None) => Ok(Vec::new()),
+            (DigestAlgorithm::Sha256, Some(_)) => Err(LayoutError::FileError(
+                "'key_file' is only used by 'hmac-sha256'".to_string(),
+            )),
+            (DigestAlgorithm::HmacSha256, None) => Err(LayoutError::FileError(
+                "'hmac-sha256' requires a 'key_file'".to_string(),
+            )),
+            (DigestAlgorithm::HmacSha256, Some(file)) => {
+                let key = std::fs::read(file).map_err(|_| {
+                    LayoutError::FileError(format!("failed to open key file: {}", file.display()))
+                })?;
+                if key.is_empty() {
+                    return Err(LayoutError::FileError(format!(
+                        "key file {} is empty",
+                        file.display()
+                    )));
+                }
+                Ok(key)
+            }
+        }
+    }
+}
+
+/// Block-level overrides of the layout settings; unset fields keep the layout value.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsOverride {
+    pub endianness: Option<Endianness>,
+    pub virtual_offset: Option<u32>,
+    pub byte_swap: Option<bool>,
+    pub pad_to_end: Option<bool>,
+    #[serde(default)]
+    pub crc: CrcOverride,
+    /// A digest table replaces the layout's digest; `false` turns it off.
+    #[serde(default, deserialize_with = "deserialize_digest_override")]
+    pub digest: Option<Option<DigestData>>,
+}
+
+fn deserialize_digest_override<'de, D>(
+    deserializer: D,
+) -> Result<Option<Option<DigestData>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct OverrideVisitor;
+
+    impl<'de> Visitor<'de> for OverrideVisitor {
+        type Value = Option<DigestData>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a digest table, or false to turn the digest off")
+        }
+
+        fn visit_bool<E: de::Error>(self, enabled: bool) -> Result<Option<DigestData>, E> {
+            match enabled {
+                false => Ok(None),
+                true => Err(E::custom(
+                    "'digest = true' is not valid; give a digest table to enable one",
+                )),
+            }
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Option<DigestData>, A::Error> {
+            DigestData::deserialize(de::value::MapAccessDeserializer::new(map)).map(Some)
+        }
+    }
+
+    deserializer.deserialize_any(OverrideVisitor).map(Some)
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrcOverride {
+    /// Catalogued algorithm replacing the layout's; the other keys still apply on top.
+    #[serde(default, deserialize_with = "deserialize_preset")]
+    pub preset: Option<&'static CrcData>,
+    pub width: Option<CrcWidth>,
+    pub polynomial: Option<u64>,
+    pub start: Option<u64>,
+    pub xor_out: Option<u64>,
+    pub ref_in: Option<bool>,
+    pub ref_out: Option<bool>,
+    pub area: Option<CrcArea>,
+}
+
+impl Settings {
+    /// Returns these settings with the given block overrides applied.
+    pub fn with_overrides(&self, overrides: &SettingsOverride) -> Settings {
+        let crc = &overrides.crc;
+        let base = crc.preset.unwrap_or(&self.crc);
+        Settings {
+            endianness: overrides.endianness.unwrap_or(self.endianness),
+            virtual_offset: overrides.virtual_offset.unwrap_or(self.virtual_offset),
+            byte_swap: overrides.byte_swap.unwrap_or(self.byte_swap),
+            pad_to_end: overrides.pad_to_end.unwrap_or(self.pad_to_end),
+            crc: CrcData {
+                width: crc.width.unwrap_or(base.width),
+                polynomial: crc.polynomial.unwrap_or(base.polynomial),
+                start: crc.start.unwrap_or(base.start),
+                xor_out: crc.xor_out.unwrap_or(base.xor_out),
+                ref_in: crc.ref_in.unwrap_or(base.ref_in),
+                ref_out: crc.ref_out.unwrap_or(base.ref_out),
+                area: crc.area.unwrap_or(self.crc.area),
+            },
+            digest: match &overrides.digest {
+                Some(digest) => digest.clone(),
+                None => self.digest.clone(),
+            },
+        }
+    }
+
+    /// Size and alignment in bytes of the slot holding the block's CRC or digest.
+    pub fn integrity_slot(&self) -> (u32, u32) {
+        match self.digest {
+            Some(_) => (32, 4),
+            None => {
+                let bytes = self.crc.width.bytes() as u32;
+                (bytes, bytes)
+            }
+        }
+    }
+}
+
 fn default_offset() -> u32 {
     0
 }
@@ -58,3 +416,11 @@ macro_rules! impl_endian_bytes {
     )*};
 }
 impl_endian_bytes!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+/// Emits the low `width` bytes of a two's complement integer in the given endianness.
+pub fn truncated_endian_bytes(value: u64, width: usize, e: &Endianness) -> Vec<u8> {
+    match e {
+        Endianness::Little => value.to_le_bytes()[..width].to_vec(),
+        Endianness::Big => value.to_be_bytes()[8 - width..].to_vec(),
+    }
+}
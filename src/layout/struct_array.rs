@@ -0,0 +1,31 @@
+use super::block::Entry;
+use super::entry::{SizeKeys, SizeSource};
+use super::errors::LayoutError;
+use serde::Deserialize;
+
+/// Array whose elements are structs, filled row by row from an Excel sheet.
+/// 'name' refers to a Main sheet cell holding '#Sheet'; member 'name' keys are column headers.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StructArrayEntry {
+    pub name: String,
+    #[serde(flatten, default)]
+    size_keys: SizeKeys,
+    pub element: Box<Entry>,
+    pub offset: Option<usize>,
+}
+
+impl StructArrayEntry {
+    /// Returns the element count and whether it must be matched exactly (SIZE).
+    pub fn count(&self) -> Result<(usize, bool), LayoutError> {
+        match self.size_keys.resolve()? {
+            (Some(SizeSource::OneD(count)), strict_len) => Ok((count, strict_len)),
+            (Some(SizeSource::TwoD(_)), _) => Err(LayoutError::DataValueExportFailed(
+                "Struct arrays take a single element count.".to_string(),
+            )),
+            (None, _) => Err(LayoutError::DataValueExportFailed(
+                "Struct arrays require 'size' or 'SIZE'.".to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::block::{Config, Entry};
+use super::entry::EntrySource;
+use super::errors::LayoutError;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+/// Reference to a named composite type from the layout's 'types' section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypeRef {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Prepended to every Excel 'name' inside the expanded type.
+    #[serde(default)]
+    pub name_prefix: String,
+    pub offset: Option<usize>,
+}
+
+impl Config {
+    /// Replaces every type reference in the blocks with a copy of the referenced type.
+    pub(crate) fn expand_types(&mut self) -> Result<(), LayoutError> {
+        for (block_name, block) in self.blocks.iter_mut() {
+            expand(&mut block.data, "", &self.types, &mut Vec::new()).map_err(|e| {
+                LayoutError::InField {
+                    field: block_name.clone(),
+                    source: Box::new(e),
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn expand(
+    entry: &mut Entry,
+    prefix: &str,
+    types: &IndexMap<String, Entry>,
+    stack: &mut Vec<String>,
+) -> Result<(), LayoutError> {
+    match entry {
+        Entry::Leaf(leaf) => {
+            prefix_source(&mut leaf.source, prefix);
+        }
+        Entry::StructArray(array) => {
+            // Element members name sheet columns, so only the table reference is prefixed.
+            array.name.insert_str(0, prefix);
+            expand(&mut array.element, "", types, stack)?;
+        }
+        Entry::Branch(branch) => {
+            for (field_name, v) in branch.fields.iter_mut() {
+                expand(v, prefix, types, stack).map_err(|e| LayoutError::InField {
+                    field: field_name.clone(),
+                    source: Box::new(e),
+                })?;
+            }
+        }
+        Entry::Type(type_ref) => {
+            if stack.contains(&type_ref.type_name) {
+                stack.push(type_ref.type_name.clone());
+                return Err(LayoutError::RecursiveType(stack.join(" -> ")));
+            }
+            let mut expanded = types
+                .get(&type_ref.type_name)
+                .ok_or_else(|| LayoutError::TypeNotFound(type_ref.type_name.clone()))?
+                .clone();
+
+            let prefix = format!("{}{}", prefix, type_ref.name_prefix);
+            stack.push(type_ref.type_name.clone());
+            expand(&mut expanded, &prefix, types, stack)?;
+            stack.pop();
+
+            if let Some(offset) = type_ref.offset {
+                match &mut expanded {
+                    Entry::Leaf(leaf) => leaf.offset = Some(offset),
+                    Entry::Branch(branch) => branch.offset = Some(offset),
+                    Entry::StructArray(array) => array.offset = Some(offset),
+                    Entry::Type(_) => {}
+                }
+            }
+            *entry = expanded;
+        }
+    }
+    Ok(())
+}
+
+fn prefix_source(source: &mut EntrySource, prefix: &str) {
+    if prefix.is_empty() {
+        return;
+    }
+    match source {
+        EntrySource::Name(name) => name.insert_str(0, prefix),
+        EntrySource::Bitfield(fields) => {
+            for field in fields.values_mut() {
+                prefix_source(&mut field.source, prefix);
+            }
+        }
+        // Computed and checksum paths are relative to the block, not the type
+        EntrySource::Value(_)
+        | EntrySource::Computed(_)
+        | EntrySource::Build(_)
+        | EntrySource::Checksum(_)
+        | EntrySource::File(_) => {}
+    }
+}
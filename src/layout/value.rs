@@ -1,17 +1,51 @@
 use super::conversions::convert_value_to_bytes;
 use super::entry::ScalarType;
 use super::errors::LayoutError;
-use super::settings::Endianness;
+use super::settings::{EndianBytes, Endianness};
+
+use indexmap::IndexMap;
 use serde::Deserialize;
+use std::fmt;
+
+/// Symbolic names of an enumeration mapped to their numeric values.
+pub type EnumTable = IndexMap<String, i64>;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum ValueSource {
     Single(DataValue),
     Array(Vec<DataValue>),
+    Matrix(Vec<Vec<DataValue>>),
+}
+
+/// Byte encoding of string values in u8 (or u16 for UTF-16) arrays.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StringEncoding {
+    /// Raw UTF-8 bytes.
+    #[default]
+    Utf8,
+    /// UTF-8 bytes, rejecting any non-ASCII character.
+    Ascii,
+    /// UTF-8 bytes followed by a NUL terminator that must fit in the array.
+    Cstring,
+    /// u8 length prefix followed by the UTF-8 bytes.
+    Pascal8,
+    /// u16 length prefix in block endianness followed by the UTF-8 bytes.
+    Pascal16,
+    /// UTF-16 little endian code units.
+    Utf16le,
+    /// UTF-16 big endian code units.
+    Utf16be,
+}
+
+impl StringEncoding {
+    pub fn is_utf16(&self) -> bool {
+        matches!(self, StringEncoding::Utf16le | StringEncoding::Utf16be)
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum DataValue {
     U64(u64),
@@ -20,22 +54,121 @@ pub enum DataValue {
     Str(String),
 }
 
+impl fmt::Display for DataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataValue::U64(v) => write!(f, "{}", v),
+            DataValue::I64(v) => write!(f, "{}", v),
+            DataValue::F64(v) => write!(f, "{}", v),
+            DataValue::Str(v) => write!(f, "'{}'", v),
+        }
+    }
+}
+
 impl DataValue {
+    /// Returns the numeric value as f64, or None for strings.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DataValue::U64(v) => Some(*v as f64),
+            DataValue::I64(v) => Some(*v as f64),
+            DataValue::F64(v) => Some(*v),
+            DataValue::Str(_) => None,
+        }
+    }
+
     pub fn to_bytes(
         &self,
         scalar_type: ScalarType,
         endianness: &Endianness,
         strict: bool,
+        tolerance: Option<f64>,
     ) -> Result<Vec<u8>, LayoutError> {
-        convert_value_to_bytes(self, scalar_type, endianness, strict)
+        convert_value_to_bytes(self, scalar_type, endianness, strict, tolerance)
     }
 
-    pub fn string_to_bytes(&self) -> Result<Vec<u8>, LayoutError> {
+    /// Resolves a symbolic name through the given enum; numbers are passed through unchanged.
+    pub fn resolve_enum(
+        &self,
+        enum_name: &str,
+        enums: &IndexMap<String, EnumTable>,
+    ) -> Result<DataValue, LayoutError> {
+        let table = enums.get(enum_name).ok_or_else(|| {
+            LayoutError::DataValueExportFailed(format!("Enum '{}' is not defined", enum_name))
+        })?;
         match self {
-            DataValue::Str(val) => Ok(val.as_bytes().to_vec()),
-            _ => Err(LayoutError::DataValueExportFailed(
+            DataValue::Str(name) => table
+                .get(name.trim())
+                .map(|v| DataValue::I64(*v))
+                .ok_or_else(|| {
+                    let allowed: Vec<_> = table.keys().map(|k| k.as_str()).collect();
+                    LayoutError::DataValueExportFailed(format!(
+                        "'{}' is not a member of enum '{}'. Allowed names: {}",
+                        name,
+                        enum_name,
+                        allowed.join(", ")
+                    ))
+                }),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    pub fn string_to_bytes(
+        &self,
+        encoding: StringEncoding,
+        endianness: &Endianness,
+    ) -> Result<Vec<u8>, LayoutError> {
+        let DataValue::Str(val) = self else {
+            return Err(LayoutError::DataValueExportFailed(
                 "String expected for string type.".to_string(),
-            )),
+            ));
+        };
+
+        let bytes = val.as_bytes();
+        match encoding {
+            StringEncoding::Utf8 => Ok(bytes.to_vec()),
+            StringEncoding::Ascii => {
+                if let Some((pos, c)) = val.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+                    return Err(LayoutError::DataValueExportFailed(format!(
+                        "Non-ASCII character '{}' at position {} in \"{}\"",
+                        c, pos, val
+                    )));
+                }
+                Ok(bytes.to_vec())
+            }
+            StringEncoding::Cstring => {
+                if bytes.contains(&0) {
+                    return Err(LayoutError::DataValueExportFailed(
+                        "String contains an embedded NUL character".to_string(),
+                    ));
+                }
+                let mut out = bytes.to_vec();
+                out.push(0);
+                Ok(out)
+            }
+            StringEncoding::Pascal8 => {
+                let len = u8::try_from(bytes.len()).map_err(|_| {
+                    LayoutError::DataValueExportFailed(format!(
+                        "String of {} bytes is too long for a u8 length prefix",
+                        bytes.len()
+                    ))
+                })?;
+                let mut out = vec![len];
+                out.extend_from_slice(bytes);
+                Ok(out)
+            }
+            StringEncoding::Pascal16 => {
+                let len = u16::try_from(bytes.len()).map_err(|_| {
+                    LayoutError::DataValueExportFailed(format!(
+                        "String of {} bytes is too long for a u16 length prefix",
+                        bytes.len()
+                    ))
+                })?;
+                let mut out = len.to_endian_bytes(endianness);
+                out.extend_from_slice(bytes);
+                Ok(out)
+            }
+            StringEncoding::Utf16le => Ok(val.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            StringEncoding::Utf16be => Ok(val.encode_utf16().flat_map(u16::to_be_bytes).collect()),
         }
     }
 }
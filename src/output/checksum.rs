@@ -1,67 +1,236 @@
-use crate::layout::settings::CrcData;
+use crate::layout::settings::{CrcData, DigestAlgorithm};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Table-driven (slicing-by-8) CRC calculation for 8, 16, 32 and 64 bit registers.
+/// Tables are built on first use for each polynomial and cached, so each block can
+/// use its own CRC settings. Results are identical to `calculate_crc_bitwise`.
+pub fn calculate_crc(data: &[u8], crc_settings: &CrcData) -> u64 {
+    let width = crc_settings.width.bits();
+    let mask = crc_settings.width.mask();
+    let reflect = |value: u64| value.reverse_bits() >> (64 - width);
+    let tables = tables_for(crc_settings);
+    let t = &tables.0;
+
+    let mut crc = if crc_settings.ref_in {
+        // Register in the low bits, least significant bit first
+        let mut crc = reflect(crc_settings.start & mask);
+        let mut chunks = data.chunks_exact(8);
+        for chunk in &mut chunks {
+            let x = crc ^ u64::from_le_bytes(chunk.try_into().unwrap());
+            crc = t[7][(x & 0xFF) as usize]
+                ^ t[6][(x >> 8 & 0xFF) as usize]
+                ^ t[5][(x >> 16 & 0xFF) as usize]
+                ^ t[4][(x >> 24 & 0xFF) as usize]
+                ^ t[3][(x >> 32 & 0xFF) as usize]
+                ^ t[2][(x >> 40 & 0xFF) as usize]
+                ^ t[1][(x >> 48 & 0xFF) as usize]
+                ^ t[0][(x >> 56) as usize];
+        }
+        for &byte in chunks.remainder() {
+            crc = t[0][((crc ^ byte as u64) & 0xFF) as usize] ^ (crc >> 8);
+        }
+        crc
+    } else {
+        // Register in the high bits so that every width shifts out of bit 63
+        let mut crc = (crc_settings.start & mask) << (64 - width);
+        let mut chunks = data.chunks_exact(8);
+        for chunk in &mut chunks {
+            let x = crc ^ u64::from_be_bytes(chunk.try_into().unwrap());
+            crc = t[7][(x >> 56) as usize]
+                ^ t[6][(x >> 48 & 0xFF) as usize]
+                ^ t[5][(x >> 40 & 0xFF) as usize]
+                ^ t[4][(x >> 32 & 0xFF) as usize]
+                ^ t[3][(x >> 24 & 0xFF) as usize]
+                ^ t[2][(x >> 16 & 0xFF) as usize]
+                ^ t[1][(x >> 8 & 0xFF) as usize]
+                ^ t[0][(x & 0xFF) as usize];
+        }
+        for &byte in chunks.remainder() {
+            crc = t[0][((crc >> 56) ^ byte as u64) as usize] ^ (crc << 8);
+        }
+        crc >> (64 - width)
+    };
+
+    // Finalize
+    if crc_settings.ref_in ^ crc_settings.ref_out {
+        crc = reflect(crc);
+    }
+
+    (crc ^ crc_settings.xor_out) & mask
+}
+
+/// Slicing-by-8 lookup tables: entry [j][i] is the register after byte i is
+/// followed by j zero bytes.
+struct CrcTables([[u64; 256]; 8]);
+
+impl CrcTables {
+    /// Builds tables for a polynomial that is bit-reversed into the low bits when
+    /// reflected, or shifted into the high bits otherwise.
+    fn new(poly: u64, reflected: bool) -> Self {
+        let mut t = [[0u64; 256]; 8];
+        for (i, entry) in t[0].iter_mut().enumerate() {
+            let mut crc = if reflected {
+                i as u64
+            } else {
+                (i as u64) << 56
+            };
+            for _ in 0..8 {
+                crc = if reflected {
+                    (crc >> 1) ^ ((crc & 1) * poly)
+                } else {
+                    (crc << 1) ^ ((crc >> 63) * poly)
+                };
+            }
+            *entry = crc;
+        }
+        for j in 1..8 {
+            for i in 0..256 {
+                let prev = t[j - 1][i];
+                t[j][i] = if reflected {
+                    (prev >> 8) ^ t[0][(prev & 0xFF) as usize]
+                } else {
+                    (prev << 8) ^ t[0][(prev >> 56) as usize]
+                };
+            }
+        }
+        CrcTables(t)
+    }
+}
+
+/// Cache key: register width, polynomial and input reflection.
+type TableKey = (u32, u64, bool);
+
+/// Returns the cached tables for the settings' width, polynomial and input reflection.
+fn tables_for(crc_settings: &CrcData) -> Arc<CrcTables> {
+    static CACHE: OnceLock<Mutex<HashMap<TableKey, Arc<CrcTables>>>> = OnceLock::new();
+
+    let width = crc_settings.width.bits();
+    let poly = crc_settings.polynomial & crc_settings.width.mask();
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry((width, poly, crc_settings.ref_in))
+        .or_insert_with(|| {
+            let poly = if crc_settings.ref_in {
+                poly.reverse_bits() >> (64 - width)
+            } else {
+                poly << (64 - width)
+            };
+            Arc::new(CrcTables::new(poly, crc_settings.ref_in))
+        })
+        .clone()
+}
+
+/// Hand-rolled bitwise CRC calculation for 8, 16, 32 and 64 bit registers, matching
+/// the crc crate's NoTable implementation. Kept as the reference for `calculate_crc`.
+pub fn calculate_crc_bitwise(data: &[u8], crc_settings: &CrcData) -> u64 {
+    let width = crc_settings.width.bits();
+    let mask = crc_settings.width.mask();
+    let reflect = |value: u64| value.reverse_bits() >> (64 - width);
 
-/// Hand-rolled CRC32 calculation matching the crc crate's NoTable implementation.
-/// This removes the need for static state and allows each block to use its own CRC settings.
-pub fn calculate_crc(data: &[u8], crc_settings: &CrcData) -> u32 {
     // Initialize CRC based on ref_in
     let mut crc = if crc_settings.ref_in {
-        crc_settings.start.reverse_bits()
+        reflect(crc_settings.start & mask)
     } else {
-        crc_settings.start
+        crc_settings.start & mask
     };
 
     // Prepare polynomial
     let poly = if crc_settings.ref_in {
-        crc_settings.polynomial.reverse_bits()
+        reflect(crc_settings.polynomial & mask)
     } else {
-        crc_settings.polynomial
+        crc_settings.polynomial & mask
     };
 
     // Process each byte
     for &byte in data {
         let idx = if crc_settings.ref_in {
-            (crc ^ (byte as u32)) & 0xFF
+            (crc ^ (byte as u64)) & 0xFF
         } else {
-            ((crc >> 24) ^ (byte as u32)) & 0xFF
+            ((crc >> (width - 8)) ^ (byte as u64)) & 0xFF
         };
 
         // Perform 8 rounds of bitwise CRC calculation
-        let mut step = if crc_settings.ref_in { idx } else { idx << 24 };
+        let mut step = if crc_settings.ref_in {
+            idx
+        } else {
+            idx << (width - 8)
+        };
         if crc_settings.ref_in {
             for _ in 0..8 {
                 step = (step >> 1) ^ ((step & 1) * poly);
             }
         } else {
             for _ in 0..8 {
-                step = (step << 1) ^ (((step >> 31) & 1) * poly);
+                step = ((step << 1) & mask) ^ (((step >> (width - 1)) & 1) * poly);
             }
         }
 
         crc = if crc_settings.ref_in {
             step ^ (crc >> 8)
         } else {
-            step ^ (crc << 8)
+            step ^ ((crc << 8) & mask)
         };
     }
 
     // Finalize
     if crc_settings.ref_in ^ crc_settings.ref_out {
-        crc = crc.reverse_bits();
+        crc = reflect(crc);
     }
 
-    crc ^ crc_settings.xor_out
+    (crc ^ crc_settings.xor_out) & mask
+}
+
+/// SHA-256 digest of the data.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// HMAC-SHA256 of the data under the given key.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    // HMAC accepts keys of any length, so this cannot fail
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Digest of the data with the selected algorithm; the key is ignored for plain SHA-256.
+pub fn calculate_digest(data: &[u8], algorithm: DigestAlgorithm, key: &[u8]) -> [u8; 32] {
+    match algorithm {
+        DigestAlgorithm::Sha256 => sha256(data),
+        DigestAlgorithm::HmacSha256 => hmac_sha256(key, data),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::layout::settings::CrcArea;
+    use crate::layout::settings::{CrcArea, CrcWidth};
+
+    fn crc(width: CrcWidth, polynomial: u64, start: u64, xor_out: u64, reflect: bool) -> CrcData {
+        CrcData {
+            width,
+            polynomial,
+            start,
+            xor_out,
+            ref_in: reflect,
+            ref_out: reflect,
+            area: CrcArea::Data,
+        }
+    }
 
     // Verify our CRC32 implementation against the well-known test vector
     // This tests the standard CRC32 settings used in all project examples
     #[test]
     fn test_crc32_standard_test_vector() {
         let crc_settings = CrcData {
+            width: CrcWidth::W32,
             polynomial: 0x04C11DB7,
             start: 0xFFFF_FFFF,
             xor_out: 0xFFFF_FFFF,
@@ -88,6 +257,7 @@ mod tests {
     #[test]
     fn test_crc32_mpeg2_non_reflected_vector() {
         let crc_settings = CrcData {
+            width: CrcWidth::W32,
             polynomial: 0x04C11DB7,
             start: 0xFFFF_FFFF,
             xor_out: 0x0000_0000,
@@ -104,4 +274,81 @@ mod tests {
             "CRC32/MPEG-2 test vector failed (expected 0x0376E6E7 for \"123456789\")"
         );
     }
+
+    #[test]
+    fn crc_widths_match_published_check_values() {
+        let check = b"123456789";
+        // CRC-8/SAE-J1850
+        let sae_j1850 = crc(CrcWidth::W8, 0x1D, 0xFF, 0xFF, false);
+        assert_eq!(calculate_crc(check, &sae_j1850), 0x4B);
+        // CRC-16/IBM-3740, commonly called CRC-16/CCITT
+        let ccitt = crc(CrcWidth::W16, 0x1021, 0xFFFF, 0, false);
+        assert_eq!(calculate_crc(check, &ccitt), 0x29B1);
+        // CRC-16/ARC
+        let arc = crc(CrcWidth::W16, 0x8005, 0, 0, true);
+        assert_eq!(calculate_crc(check, &arc), 0xBB3D);
+        // CRC-64/XZ
+        let xz = crc(CrcWidth::W64, 0x42F0E1EBA9EA3693, u64::MAX, u64::MAX, true);
+        assert_eq!(calculate_crc(check, &xz), 0x995DC9BBDF1939FA);
+        // CRC-64/ECMA-182
+        let ecma = crc(CrcWidth::W64, 0x42F0E1EBA9EA3693, 0, 0, false);
+        assert_eq!(calculate_crc(check, &ecma), 0x6C40DF5F0B497347);
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn sha256_test_vector() {
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn hmac_sha256_rfc4231_vector() {
+        // RFC 4231 test case 1
+        let key = [0x0b; 20];
+        assert_eq!(
+            hex(&hmac_sha256(&key, b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+    }
+
+    #[test]
+    fn table_driven_crc_matches_bitwise_for_every_preset() {
+        // Pseudo-random buffer so every table slot and tail length gets exercised
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let data: Vec<u8> = (0..4099)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        let lengths = (0..=40).chain([63, 64, 65, 1000, 4096, 4099]);
+
+        for entry in crate::layout::crc_catalogue::CATALOGUE {
+            for len in lengths.clone() {
+                assert_eq!(
+                    calculate_crc(&data[..len], &entry.crc),
+                    calculate_crc_bitwise(&data[..len], &entry.crc),
+                    "{} differs over {} bytes",
+                    entry.name,
+                    len
+                );
+            }
+        }
+
+        // Mixed reflection is not in the catalogue
+        let mut mixed = crc(CrcWidth::W16, 0x8005, 0x1234, 0x0F0F, true);
+        mixed.ref_out = false;
+        assert_eq!(
+            calculate_crc(&data, &mixed),
+            calculate_crc_bitwise(&data, &mixed)
+        );
+    }
 }
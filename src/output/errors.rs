@@ -10,4 +10,7 @@ pub enum OutputError {
 
     #[error("Block memory overlap detected: {0}")]
     BlockOverlapError(String),
+
+    #[error(transparent)]
+    Layout(#[from] crate::layout::errors::LayoutError),
 }
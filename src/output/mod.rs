@@ -3,7 +3,7 @@ pub mod checksum;
 pub mod errors;
 
 use crate::layout::header::{CrcLocation, Header};
-use crate::layout::settings::{CrcArea, Endianness, Settings};
+use crate::layout::settings::{CrcArea, Settings, truncated_endian_bytes};
 use crate::output::args::OutputFormat;
 use errors::OutputError;
 
@@ -25,7 +25,12 @@ fn byte_swap_inplace(bytes: &mut [u8]) {
     }
 }
 
-fn validate_crc_location(length: usize, header: &Header) -> Result<u32, OutputError> {
+fn validate_crc_location(
+    length: usize,
+    header: &Header,
+    slot_size: u32,
+    slot_align: u32,
+) -> Result<u32, OutputError> {
     let crc_offset = match &header.crc_location {
         CrcLocation::Address(address) => {
             let crc_offset = address.checked_sub(header.start_address).ok_or_else(|| {
@@ -41,7 +46,7 @@ fn validate_crc_location(length: usize, header: &Header) -> Result<u32, OutputEr
             crc_offset
         }
         CrcLocation::Keyword(option) => match option.as_str() {
-            "end" => (length as u32 + 3) & !3,
+            "end" => (length as u32).next_multiple_of(slot_align),
             _ => {
                 return Err(OutputError::HexOutputError(format!(
                     "Invalid CRC location: {}",
@@ -51,7 +56,7 @@ fn validate_crc_location(length: usize, header: &Header) -> Result<u32, OutputEr
         },
     };
 
-    if header.length < crc_offset + 4 {
+    if header.length < crc_offset + slot_size {
         return Err(OutputError::HexOutputError(
             "CRC location would overrun block.".to_string(),
         ));
@@ -76,16 +81,24 @@ pub fn bytestream_to_datarange(
 
     // Apply optional byte swap across the entire stream before CRC
     if byte_swap {
-        if bytestream.len() % 2 != 0 {
+        if !bytestream.len().is_multiple_of(2) {
             bytestream.push(header.padding);
         }
         byte_swap_inplace(bytestream.as_mut_slice());
     }
 
+    // The slot holds the CRC at its width, or a 32 byte digest
+    let (slot_size, slot_align) = settings.integrity_slot();
+    let area = match &settings.digest {
+        Some(digest) => digest.area,
+        None => settings.crc.area,
+    };
+
     // Determine CRC location relative to current payload end
-    let crc_location = validate_crc_location(bytestream.len(), header)?;
+    let crc_location = validate_crc_location(bytestream.len(), header, slot_size, slot_align)?;
 
-    let used_size = ((bytestream.len() as u32).saturating_add(4)).saturating_sub(padding_bytes);
+    let used_size =
+        ((bytestream.len() as u32).saturating_add(slot_size)).saturating_sub(padding_bytes);
     let allocated_size = header.length;
 
     // Padding for CRC alignment
@@ -94,17 +107,20 @@ pub fn bytestream_to_datarange(
     }
 
     // Fill whole block if the CRC area is block
-    if settings.crc.area == CrcArea::Block {
+    if area == CrcArea::Block {
         bytestream.resize(header.length as usize, header.padding);
-        bytestream[crc_location as usize..(crc_location + 4) as usize].fill(0);
+        bytestream[crc_location as usize..(crc_location + slot_size) as usize].fill(0);
     }
 
-    // Compute CRC based on selected area
-    let crc_val = checksum::calculate_crc(&bytestream, &settings.crc);
-
-    let mut crc_bytes: [u8; 4] = match settings.endianness {
-        Endianness::Big => crc_val.to_be_bytes(),
-        Endianness::Little => crc_val.to_le_bytes(),
+    // Compute CRC or digest based on selected area; digests are stored as produced
+    let mut crc_bytes = match &settings.digest {
+        Some(digest) => {
+            checksum::calculate_digest(&bytestream, digest.algorithm, &digest.key()?).to_vec()
+        }
+        None => {
+            let crc_val = checksum::calculate_crc(&bytestream, &settings.crc);
+            truncated_endian_bytes(crc_val, slot_size as usize, &settings.endianness)
+        }
     };
     if byte_swap {
         byte_swap_inplace(&mut crc_bytes);
@@ -119,7 +135,7 @@ pub fn bytestream_to_datarange(
         start_address: header.start_address + settings.virtual_offset,
         bytestream,
         crc_address: header.start_address + settings.virtual_offset + crc_location,
-        crc_bytestream: crc_bytes.to_vec(),
+        crc_bytestream: crc_bytes,
         used_size,
         allocated_size,
     })
@@ -200,13 +216,14 @@ mod tests {
     use crate::layout::header::Header;
     use crate::layout::settings::Endianness;
     use crate::layout::settings::Settings;
-    use crate::layout::settings::{CrcArea, CrcData};
+    use crate::layout::settings::{CrcArea, CrcData, CrcWidth};
 
     fn sample_settings() -> Settings {
         Settings {
             endianness: Endianness::Little,
             virtual_offset: 0,
             crc: CrcData {
+                width: CrcWidth::W32,
                 polynomial: 0x04C11DB7,
                 start: 0xFFFF_FFFF,
                 xor_out: 0xFFFF_FFFF,
@@ -216,6 +233,7 @@ mod tests {
             },
             byte_swap: false,
             pad_to_end: false,
+            digest: None,
         }
     }
 
@@ -225,6 +243,7 @@ mod tests {
             length: len,
             crc_location: CrcLocation::Keyword("end".to_string()),
             padding: 0xFF,
+            max_alignment: None,
         }
     }
 
@@ -243,12 +262,12 @@ mod tests {
         assert_eq!(bytestream.len(), 4);
 
         // And the emitted hex should contain the CRC bytes (endianness applied)
-        let crc_location = super::validate_crc_location(4usize, &header).expect("crc loc");
+        let crc_location = super::validate_crc_location(4usize, &header, 4, 4).expect("crc loc");
         assert_eq!(crc_location as usize, 4, "crc should follow payload end");
         let crc_val = checksum::calculate_crc(&bytestream[..crc_location as usize], &settings.crc);
         let crc_bytes = match settings.endianness {
-            Endianness::Big => crc_val.to_be_bytes(),
-            Endianness::Little => crc_val.to_le_bytes(),
+            Endianness::Big => (crc_val as u32).to_be_bytes(),
+            Endianness::Little => (crc_val as u32).to_le_bytes(),
         };
         // No byte swap in this test
         let expected_crc_ascii = crc_bytes
@@ -8,6 +8,9 @@ use std::collections::HashMap;
 use crate::layout::value::{DataValue, ValueSource};
 use errors::VariantError;
 
+/// One row of a struct array sheet, keyed by column header.
+pub type TableRow = HashMap<String, DataValue>;
+
 pub struct DataSheet {
     names: Vec<String>,
     default_values: Vec<Data>,
@@ -119,8 +122,9 @@ impl DataSheet {
         let result = (|| match self.retrieve_cell(name)? {
             Data::Int(i) => Ok(DataValue::I64(*i)),
             Data::Float(f) => Ok(DataValue::F64(*f)),
+            Data::String(s) => Ok(DataValue::Str(s.to_owned())),
             _ => Err(VariantError::RetrievalError(
-                "Found non-numeric single value".to_string(),
+                "Found unsupported single value".to_string(),
             )),
         })();
 
@@ -190,26 +194,13 @@ impl DataSheet {
                 ));
             };
 
-            let sheet_name = cell_string.strip_prefix('#').ok_or_else(|| {
-                VariantError::RetrievalError(format!(
-                    "2D array reference must start with '#' prefix, got: {}",
-                    cell_string
-                ))
-            })?;
-
-            let sheet = self.sheets.get(sheet_name).ok_or_else(|| {
-                let available: Vec<_> = self.sheets.keys().map(|s| s.as_str()).collect();
-                VariantError::RetrievalError(format!(
-                    "Sheet not found: '{}'. Available sheets: {}",
-                    sheet_name,
-                    available.join(", ")
-                ))
-            })?;
+            let sheet = self.referenced_sheet(cell_string, "2D array")?;
 
             let convert = |cell: &Data| -> Result<DataValue, VariantError> {
                 match cell {
                     Data::Int(i) => Ok(DataValue::I64(*i)),
                     Data::Float(f) => Ok(DataValue::F64(*f)),
+                    Data::String(s) => Ok(DataValue::Str(s.to_owned())),
                     _ => Err(VariantError::RetrievalError(
                         "Unsupported data type in 2D array".to_string(),
                     )),
@@ -256,6 +247,84 @@ impl DataSheet {
         })
     }
 
+    /// Retrieves the rows of a sheet referenced as '#Sheet', keyed by the header row.
+    /// Reading stops at the first row with an empty first cell; empty cells are omitted.
+    pub fn retrieve_table(&self, name: &str) -> Result<Vec<TableRow>, VariantError> {
+        let result = (|| {
+            let Data::String(cell_string) = self.retrieve_cell(name)? else {
+                return Err(VariantError::RetrievalError(
+                    "Expected string value for struct array".to_string(),
+                ));
+            };
+
+            let sheet = self.referenced_sheet(cell_string, "Struct array")?;
+
+            let mut rows = sheet.rows();
+            let hdrs = rows.next().ok_or_else(|| {
+                VariantError::RetrievalError("No headers found in struct array".to_string())
+            })?;
+            let headers: Vec<String> = hdrs
+                .iter()
+                .take_while(|c| !Self::cell_is_empty(c))
+                .map(|c| c.to_string().trim().to_string())
+                .collect();
+
+            let mut out = Vec::new();
+            for row in rows {
+                if row.first().is_none_or(Self::cell_is_empty) {
+                    break;
+                }
+
+                let mut values = TableRow::with_capacity(headers.len());
+                for (header, cell) in headers.iter().zip(row.iter()) {
+                    let v = match cell {
+                        Data::Int(i) => DataValue::I64(*i),
+                        Data::Float(f) => DataValue::F64(*f),
+                        Data::String(s) if !s.trim().is_empty() => DataValue::Str(s.to_owned()),
+                        Data::Empty | Data::String(_) => continue,
+                        _ => {
+                            return Err(VariantError::RetrievalError(format!(
+                                "Unsupported data type in column '{}'",
+                                header
+                            )));
+                        }
+                    };
+                    values.insert(header.clone(), v);
+                }
+                out.push(values);
+            }
+
+            Ok(out)
+        })();
+
+        result.map_err(|e| VariantError::WhileRetrieving {
+            name: name.to_string(),
+            source: Box::new(e),
+        })
+    }
+
+    fn referenced_sheet(
+        &self,
+        cell_string: &str,
+        kind: &str,
+    ) -> Result<&Range<Data>, VariantError> {
+        let sheet_name = cell_string.strip_prefix('#').ok_or_else(|| {
+            VariantError::RetrievalError(format!(
+                "{} reference must start with '#' prefix, got: {}",
+                kind, cell_string
+            ))
+        })?;
+
+        self.sheets.get(sheet_name).ok_or_else(|| {
+            let available: Vec<_> = self.sheets.keys().map(|s| s.as_str()).collect();
+            VariantError::RetrievalError(format!(
+                "Sheet not found: '{}'. Available sheets: {}",
+                sheet_name,
+                available.join(", ")
+            ))
+        })
+    }
+
     fn retrieve_cell(&self, name: &str) -> Result<&Data, VariantError> {
         let index =
             self.names
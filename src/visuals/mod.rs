@@ -52,7 +52,7 @@ pub fn print_detailed(stats: &BuildStats) {
             Cell::new("Address Range").add_attribute(Attribute::Bold),
             Cell::new("Used/Alloc").add_attribute(Attribute::Bold),
             Cell::new("Efficiency").add_attribute(Attribute::Bold),
-            Cell::new("CRC Value").add_attribute(Attribute::Bold),
+            Cell::new("CRC/Digest").add_attribute(Attribute::Bold),
         ]);
 
     for block in &stats.block_stats {
@@ -68,7 +68,14 @@ pub fn print_detailed(stats: &BuildStats) {
                 format_bytes(block.allocated_size as usize)
             )),
             Cell::new(format_efficiency(block.used_size, block.allocated_size)),
-            Cell::new(format!("0x{:08X}", block.crc_value)),
+            Cell::new(match &block.digest {
+                Some(digest) => digest.iter().map(|b| format!("{:02x}", b)).collect(),
+                None => format!(
+                    "0x{:0width$X}",
+                    block.crc_value,
+                    width = block.crc_width.bytes() * 2
+                ),
+            }),
         ]);
     }
 
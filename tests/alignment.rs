@@ -0,0 +1,64 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout_with(max_alignment: Option<usize>, data: &str) -> String {
+    let max_alignment = max_alignment
+        .map(|m| format!("max_alignment = {}", m))
+        .unwrap_or_default();
+    common::InlineLayout {
+        padding: 0xEE,
+        header: &max_alignment,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+#[test]
+fn packed_block_has_no_alignment_padding() {
+    let layout = layout_with(
+        Some(1),
+        r#"
+a = { value = 0x11, type = "u8" }
+b = { value = 0x22334455, type = "u32" }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_align_packed", &layout, true).expect("build");
+    assert_eq!(&bytes[..5], &[0x11, 0x55, 0x44, 0x33, 0x22]);
+}
+
+#[test]
+fn max_alignment_caps_wide_types() {
+    let layout = layout_with(
+        Some(4),
+        r#"
+a = { value = 0x11, type = "u8" }
+b = { value = 1, type = "u64" }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_align_cap", &layout, true).expect("build");
+    assert_eq!(&bytes[..5], &[0x11, 0xEE, 0xEE, 0xEE, 0x01]);
+}
+
+#[test]
+fn field_align_overrides_block_packing() {
+    let layout = layout_with(
+        Some(1),
+        r#"
+a = { value = 0x11, type = "u8" }
+b = { value = 0x22, type = "u8", align = 4 }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_align_field", &layout, true).expect("build");
+    assert_eq!(&bytes[..5], &[0x11, 0xEE, 0xEE, 0xEE, 0x22]);
+}
+
+#[test]
+fn non_power_of_two_alignment_is_rejected() {
+    let layout = layout_with(None, r#"a = { value = 0x11, type = "u8", align = 3 }"#);
+
+    let err = common::build_inline_block("test_align_invalid", &layout, true).unwrap_err();
+    assert!(err.contains("not a power of two"), "got: {}", err);
+}
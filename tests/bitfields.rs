@@ -0,0 +1,60 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn build_layout(file_stem: &str, data: &str, strict: bool) -> Result<Vec<u8>, String> {
+    let layout_toml = common::InlineLayout {
+        endianness: "big",
+        ..Default::default()
+    }
+    .with_data(data);
+
+    common::build_inline_block(file_stem, &layout_toml, strict)
+}
+
+#[test]
+fn bitfield_members_pack_lsb_first() {
+    let bytes = build_layout(
+        "test_bitfield_pack",
+        r#"flags = { type = "u16", bitfield = { enable = { bits = 1, value = 1 }, mode = { bits = 3, value = 5 }, level = { bits = 8, value = 0xA5 } } }"#,
+        true,
+    )
+    .expect("bitfield should build");
+
+    // enable | mode << 1 | level << 4 = 0x0A5B, emitted big endian
+    assert_eq!(&bytes[..2], &[0x0A, 0x5B]);
+}
+
+#[test]
+fn bitfield_overflow_rejected_in_strict_mode() {
+    let data = r#"flags = { type = "u8", bitfield = { mode = { bits = 2, value = 5 }, rest = { bits = 6, value = 0 } } }"#;
+
+    let err = build_layout("test_bitfield_overflow", data, true).unwrap_err();
+    assert!(err.contains("does not fit in 2 bits"), "got: {}", err);
+
+    // Lenient mode truncates to the member width
+    let bytes = build_layout("test_bitfield_overflow_lenient", data, false)
+        .expect("lenient bitfield should build");
+    assert_eq!(bytes[0], 0x01);
+}
+
+#[test]
+fn bitfield_wider_than_storage_rejected() {
+    let err = build_layout(
+        "test_bitfield_too_wide",
+        r#"flags = { type = "u8", bitfield = { a = { bits = 4, value = 0 }, b = { bits = 5, value = 0 } } }"#,
+        false,
+    )
+    .unwrap_err();
+    assert!(err.contains("exceed the 8 bits"), "got: {}", err);
+}
+
+#[test]
+fn huge_bitfield_width_rejected() {
+    let err = build_layout(
+        "test_bitfield_width_overflow",
+        r#"flags = { type = "u8", bitfield = { a = { bits = 4, value = 0 }, b = { bits = 4294967295, value = 0 } } }"#,
+        false,
+    )
+    .unwrap_err();
+    assert!(err.contains("exceed the 8 bits"), "got: {}", err);
+}
@@ -0,0 +1,93 @@
+use nvmbuilder::commands;
+use nvmbuilder::layout::build_info::BuildInfo;
+use nvmbuilder::layout::settings::Endianness;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"
+[settings]
+endianness = "little"
+virtual_offset = 0
+byte_swap = false
+pad_to_end = false
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[host.header]
+start_address = 0x80000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[host.data]
+word = { value = 0x1234, type = "u16" }
+
+[dsp.header]
+start_address = 0x90000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[dsp.settings]
+endianness = "big"
+
+[dsp.settings.crc]
+polynomial = 0x1EDC6F41
+xor_out = 0
+
+[dsp.data]
+word = { value = 0x1234, type = "u16" }
+"#;
+
+#[test]
+fn block_settings_override_layout_settings() {
+    let path = common::write_layout_file("test_block_settings", LAYOUT);
+    let cfg = nvmbuilder::layout::load_layout(&path).expect("layout loads");
+
+    let host = cfg.blocks["host"].effective_settings(&cfg);
+    let dsp = cfg.blocks["dsp"].effective_settings(&cfg);
+    assert!(matches!(host.endianness, Endianness::Little));
+    assert!(matches!(dsp.endianness, Endianness::Big));
+    assert_eq!(dsp.crc.polynomial, 0x1EDC6F41);
+    assert_eq!(dsp.crc.xor_out, 0);
+    // Fields not overridden fall back to the layout settings
+    assert_eq!(dsp.crc.start, 0xFFFFFFFF);
+    assert!(dsp.crc.ref_in);
+
+    let (host_bytes, _) = cfg.blocks["host"]
+        .build_bytestream(None, &cfg, true, &BuildInfo::default())
+        .expect("host builds");
+    let (dsp_bytes, _) = cfg.blocks["dsp"]
+        .build_bytestream(None, &cfg, true, &BuildInfo::default())
+        .expect("dsp builds");
+    assert_eq!(&host_bytes[..2], &[0x34, 0x12]);
+    assert_eq!(&dsp_bytes[..2], &[0x12, 0x34]);
+
+    // The CRC reported for the block uses the overridden algorithm
+    let args = common::build_args(&path, "dsp", nvmbuilder::output::args::OutputFormat::Hex);
+    let input = nvmbuilder::layout::args::BlockNames {
+        name: "dsp".to_string(),
+        file: path.clone(),
+    };
+    let stat = commands::generate::build_block_single(&input, None, &BuildInfo::default(), &args)
+        .expect("dsp builds");
+
+    let expected = nvmbuilder::output::checksum::calculate_crc(&dsp_bytes, &dsp.crc);
+    assert_eq!(stat.crc_value, expected);
+}
+
+#[test]
+fn unknown_block_setting_is_rejected() {
+    let layout = LAYOUT.replace("[dsp.settings]", "[dsp.settings]\nendian = \"big\"");
+    let path = common::write_layout_file("test_block_settings_unknown", &layout);
+
+    let err = nvmbuilder::layout::load_layout(&path).unwrap_err();
+    assert!(err.to_string().contains("endian"), "got: {}", err);
+}
@@ -0,0 +1,124 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::layout::build_info::BuildInfo;
+
+fn build(file_stem: &str, data: &str, info: &BuildInfo) -> Result<Vec<u8>, String> {
+    let path = common::write_layout_file(file_stem, &common::inline_layout(data));
+    let cfg = nvmbuilder::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    cfg.blocks["block"]
+        .build_bytestream(None, &cfg, true, info)
+        .map(|(bytes, _padding)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn build_fields_insert_build_metadata() {
+    let info = BuildInfo {
+        timestamp: 1_700_000_000,
+        build_number: Some(42),
+        variant: Some("VarA".to_string()),
+        debug: true,
+        input_hash: 0xDEADBEEF,
+    };
+    let bytes = build(
+        "test_build_fields",
+        r#"
+time = { type = "u32", build = "timestamp" }
+number = { type = "u16", build = "build_number" }
+hash = { type = "u32", build = "input_hash" }
+flags = { type = "u8", bitfield = { debug = { bits = 1, build = "debug" }, spare = { bits = 7, value = 0 } } }
+iso = { type = "u8", size = 20, build = "timestamp_iso" }
+variant = { type = "u8", size = 8, build = "variant", encoding = "cstring" }
+version = { type = "u8", size = 16, build = "version" }
+"#,
+        &info,
+    )
+    .expect("build fields resolve");
+
+    assert_eq!(&bytes[..4], &1_700_000_000u32.to_le_bytes());
+    assert_eq!(&bytes[4..6], &42u16.to_le_bytes());
+    assert_eq!(&bytes[8..12], &0xDEADBEEFu32.to_le_bytes());
+    assert_eq!(bytes[12], 1);
+    assert_eq!(&bytes[13..33], b"2023-11-14T22:13:20Z");
+    assert_eq!(&bytes[33..38], b"VarA\0");
+    let version = env!("CARGO_PKG_VERSION").as_bytes();
+    assert_eq!(&bytes[41..41 + version.len()], version);
+}
+
+#[test]
+fn build_number_is_required_when_used() {
+    let err = build(
+        "test_build_number_missing",
+        r#"number = { type = "u16", build = "build_number" }"#,
+        &BuildInfo::default(),
+    )
+    .unwrap_err();
+    assert!(err.contains("--build-number"), "got: {}", err);
+}
+
+#[test]
+fn source_date_epoch_overrides_the_clock() {
+    let path = common::write_layout_file(
+        "test_build_epoch",
+        &common::inline_layout(
+            r#"
+time = { type = "u32", build = "timestamp" }
+hash = { type = "u32", build = "input_hash" }
+"#,
+        ),
+    );
+    let args = common::build_args(&path, "block", nvmbuilder::output::args::OutputFormat::Hex);
+    let unused = common::write_layout_file("test_build_epoch_unused", &common::inline_layout(""));
+    let unused_args = common::build_args(
+        &unused,
+        "block",
+        nvmbuilder::output::args::OutputFormat::Hex,
+    );
+
+    // SAFETY: no other test in this binary reads the environment.
+    unsafe { std::env::set_var("SOURCE_DATE_EPOCH", "1234567890") };
+    let first = BuildInfo::from_args(&args).expect("build info");
+    let second = BuildInfo::from_args(&args).expect("build info");
+    unsafe { std::env::set_var("SOURCE_DATE_EPOCH", "not a number") };
+    let malformed = BuildInfo::from_args(&args).map(|_| ()).unwrap_err();
+    let without_fields = BuildInfo::from_args(&unused_args);
+    unsafe { std::env::remove_var("SOURCE_DATE_EPOCH") };
+
+    assert_eq!(first.timestamp, 1_234_567_890);
+    assert_eq!(first.input_hash, second.input_hash);
+    assert_ne!(first.input_hash, 0);
+    assert!(
+        malformed.to_string().contains("SOURCE_DATE_EPOCH"),
+        "got: {}",
+        malformed
+    );
+    assert!(
+        without_fields.is_ok(),
+        "SOURCE_DATE_EPOCH is only read when used"
+    );
+}
+
+#[test]
+fn input_hash_ignores_how_the_layout_path_is_spelled() {
+    let path = common::write_layout_file(
+        "test_build_hash_spelling",
+        &common::inline_layout(
+            r#"
+hash = { type = "u32", build = "input_hash" }
+blob = { file = "test_build_hash_blob.bin", type = "u8", SIZE = 4 }
+"#,
+        ),
+    );
+    std::fs::write("out/test_build_hash_blob.bin", b"BLOB").expect("write file source");
+    let absolute = std::fs::canonicalize(&path).expect("layout path resolves");
+    let hash = |path: &str| {
+        let args = common::build_args(path, "block", nvmbuilder::output::args::OutputFormat::Hex);
+        BuildInfo::from_args(&args).expect("build info").input_hash
+    };
+
+    let relative = hash(&path);
+    assert_ne!(relative, 0);
+    assert_eq!(relative, hash(&format!("./{}", path)));
+    assert_eq!(relative, hash(absolute.to_str().unwrap()));
+}
@@ -0,0 +1,61 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn build(file_stem: &str, data: &str) -> Result<Vec<u8>, String> {
+    common::build_inline_block(file_stem, &common::inline_layout(data), true)
+}
+
+#[test]
+fn hex_and_base64_literals_fill_u8_arrays() {
+    let bytes = build(
+        "test_byte_literals",
+        r#"
+key = { value = "hex:DEADBEEF0102", type = "u8", SIZE = 6 }
+mac = { value = "hex:00:1A:2B:3C:4D:5E", type = "u8", size = 8 }
+blob = { value = "base64:3q2+7w==", type = "u8", SIZE = 4 }
+"#,
+    )
+    .expect("byte literals build");
+
+    assert_eq!(&bytes[..6], &[0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x02]);
+    // 'size' pads short literals with the block padding
+    assert_eq!(
+        &bytes[6..14],
+        &[0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E, 0xFF, 0xFF]
+    );
+    assert_eq!(&bytes[14..18], &[0xDE, 0xAD, 0xBE, 0xEF]);
+}
+
+#[test]
+fn byte_literal_length_is_checked() {
+    let err = build(
+        "test_byte_literal_long",
+        r#"key = { value = "hex:0102030405", type = "u8", size = 4 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("larger than defined size"), "got: {}", err);
+
+    let err = build(
+        "test_byte_literal_short",
+        r#"key = { value = "base64:AQID", type = "u8", SIZE = 4 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("strict SIZE"), "got: {}", err);
+}
+
+#[test]
+fn malformed_byte_literals_are_rejected() {
+    let err = build(
+        "test_byte_literal_odd",
+        r#"key = { value = "hex:ABC", type = "u8", size = 4 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("odd number of digits"), "got: {}", err);
+
+    let err = build(
+        "test_byte_literal_type",
+        r#"key = { value = "hex:0102", type = "u16", size = 2 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("Byte literals need type u8"), "got: {}", err);
+}
@@ -5,6 +5,7 @@ use std::path::Path;
 
 use nvmbuilder::args::Args;
 use nvmbuilder::layout::args::{BlockNames, LayoutArgs};
+use nvmbuilder::layout::build_info::BuildInfo;
 use nvmbuilder::output::args::{OutputArgs, OutputFormat};
 use nvmbuilder::variant::{self, DataSheet};
 
@@ -27,6 +28,7 @@ pub fn build_args(layout_path: &str, block_name: &str, format: OutputFormat) ->
                 file: layout_path.to_string(),
             }],
             strict: false,
+            build_number: None,
         },
         variant: variant::args::VariantArgs {
             xlsx: Some("examples/data.xlsx".to_string()),
@@ -96,6 +98,7 @@ pub fn build_args_for_layouts(layouts: Vec<BlockNames>, format: OutputFormat) ->
         layout: LayoutArgs {
             blocks: layouts,
             strict: false,
+            build_number: None,
         },
         variant: variant::args::VariantArgs {
             xlsx: Some("examples/data.xlsx".to_string()),
@@ -115,3 +118,87 @@ pub fn build_args_for_layouts(layouts: Vec<BlockNames>, format: OutputFormat) ->
         },
     }
 }
+
+/// Writes an inline layout, loads it and builds the block named 'block' without a datasheet.
+pub fn build_inline_block(
+    file_stem: &str,
+    contents: &str,
+    strict: bool,
+) -> Result<Vec<u8>, String> {
+    let path = write_layout_file(file_stem, contents);
+    let cfg = nvmbuilder::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let block = cfg.blocks.get("block").expect("block present");
+    block
+        .build_bytestream(None, &cfg, strict, &BuildInfo::default())
+        .map(|(bytes, _padding)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+/// Single-block layout shared by the feature tests: the standard CRC32 settings and a
+/// block named 'block' at 0x80000, with the given data entries.
+pub struct InlineLayout<'a> {
+    pub endianness: &'a str,
+    pub virtual_offset: u32,
+    pub length: u32,
+    pub padding: u8,
+    /// Extra keys for the block header, such as `max_alignment`.
+    pub header: &'a str,
+    /// Extra top-level tables, such as `[enums.*]`, `[types.*]` or `[block.settings]`.
+    pub tables: &'a str,
+}
+
+impl Default for InlineLayout<'_> {
+    fn default() -> Self {
+        InlineLayout {
+            endianness: "little",
+            virtual_offset: 0,
+            length: 0x100,
+            padding: 0xFF,
+            header: "",
+            tables: "",
+        }
+    }
+}
+
+impl InlineLayout<'_> {
+    pub fn with_data(&self, data: &str) -> String {
+        format!(
+            r#"
+[settings]
+endianness = "{}"
+virtual_offset = {:#X}
+byte_swap = false
+pad_to_end = false
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+{}
+[block.header]
+start_address = 0x80000
+length = {:#X}
+crc_location = "end"
+padding = {:#04X}
+{}
+[block.data]
+{}
+"#,
+            self.endianness,
+            self.virtual_offset,
+            self.tables,
+            self.length,
+            self.padding,
+            self.header,
+            data
+        )
+    }
+}
+
+/// Default inline layout around the given data entries.
+pub fn inline_layout(data: &str) -> String {
+    InlineLayout::default().with_data(data)
+}
@@ -0,0 +1,75 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout(data: &str) -> String {
+    common::InlineLayout {
+        virtual_offset: 0x1000,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+fn build(file_stem: &str, data: &str) -> Result<Vec<u8>, String> {
+    common::build_inline_block(file_stem, &layout(data), true)
+}
+
+#[test]
+fn sizeof_offsetof_and_address_of_resolve_forward_references() {
+    let bytes = build(
+        "test_computed_fields",
+        r#"
+header.length = { type = "u32", computed = "sizeof()" }
+header.cal_offset = { type = "u16", computed = "offsetof(calibration)" }
+header.cal_words = { type = "u16", computed = "sizeof(calibration) / 2" }
+header.matrix_ptr = { type = "u32", computed = "address_of(calibration.matrix)" }
+calibration.gain = { value = 1, type = "u8" }
+calibration.matrix = { value = [[1, 2], [3, 4]], type = "u16", size = [2, 2] }
+"#,
+    )
+    .expect("computed fields resolve");
+
+    assert_eq!(&bytes[..4], &22u32.to_le_bytes());
+    assert_eq!(&bytes[4..6], &12u16.to_le_bytes());
+    assert_eq!(&bytes[6..8], &5u16.to_le_bytes());
+    // start_address + virtual_offset + offset of the matrix after gain and padding
+    assert_eq!(&bytes[8..12], &0x8100Eu32.to_le_bytes());
+    assert_eq!(&bytes[12..14], &[1, 0xFF]);
+}
+
+#[test]
+fn computed_values_are_range_checked() {
+    let err = build(
+        "test_computed_overflow",
+        r#"
+ptr = { type = "u16", computed = "address_of(data)" }
+data = { value = 1, type = "u8" }
+"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("out of range for u16"), "got: {}", err);
+}
+
+#[test]
+fn unknown_paths_and_functions_are_rejected() {
+    let err = build(
+        "test_computed_unknown_path",
+        r#"ptr = { type = "u32", computed = "address_of(missing.field)" }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("Unknown field 'missing.field' in address_of()"),
+        "got: {}",
+        err
+    );
+
+    let err = build(
+        "test_computed_in_literal",
+        r#"len = { type = "u32", value = "sizeof()" }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("only available in 'computed' fields"),
+        "got: {}",
+        err
+    );
+}
@@ -0,0 +1,108 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::layout::build_info::BuildInfo;
+use nvmbuilder::layout::crc_catalogue;
+use nvmbuilder::layout::settings::{CrcArea, CrcWidth};
+use nvmbuilder::output::checksum::calculate_crc;
+
+const BLOCK: &str = r#"
+[block.header]
+start_address = 0x80000
+length = 0x40
+crc_location = "end"
+padding = 0xFF
+
+[block.data]
+word = { value = 0x1234, type = "u16" }
+"#;
+
+fn load(file_stem: &str, settings: &str) -> Result<nvmbuilder::layout::block::Config, String> {
+    let layout = format!(
+        "[settings]\nendianness = \"little\"\n{}\n{}",
+        settings, BLOCK
+    );
+    let path = common::write_layout_file(file_stem, &layout);
+    nvmbuilder::layout::load_layout(&path).map_err(|e| e.to_string())
+}
+
+#[test]
+fn crc_can_be_given_as_a_preset_name() {
+    let cfg = load("test_crc_preset_name", "crc = \"CRC-16/XMODEM\"").expect("layout loads");
+    assert_eq!(cfg.settings.crc.width, CrcWidth::W16);
+    assert_eq!(cfg.settings.crc.polynomial, 0x1021);
+    assert_eq!(cfg.settings.crc.start, 0);
+    assert!(!cfg.settings.crc.ref_in);
+    assert_eq!(calculate_crc(b"123456789", &cfg.settings.crc), 0x31C3);
+}
+
+#[test]
+fn preset_table_keys_override_the_catalogue() {
+    let cfg = load(
+        "test_crc_preset_table",
+        "[settings.crc]\npreset = \"CRC-32/ISO-HDLC\"\nxor_out = 0\narea = \"block\"",
+    )
+    .expect("layout loads");
+
+    let iso_hdlc = &crc_catalogue::find("CRC-32/ISO-HDLC").unwrap().crc;
+    assert_eq!(cfg.settings.crc.polynomial, iso_hdlc.polynomial);
+    assert_eq!(cfg.settings.crc.start, iso_hdlc.start);
+    assert_eq!(cfg.settings.crc.xor_out, 0);
+    assert_eq!(cfg.settings.crc.area, CrcArea::Block);
+}
+
+#[test]
+fn block_settings_can_switch_preset() {
+    let settings =
+        "crc = \"CRC-32/ISO-HDLC\"\n\n[block.settings.crc]\npreset = \"CRC-8/SAE-J1850\"";
+    let cfg = load("test_crc_preset_block", settings).expect("layout loads");
+
+    let crc = cfg.blocks["block"].effective_settings(&cfg).crc;
+    assert_eq!(crc.width, CrcWidth::W8);
+    assert_eq!(calculate_crc(b"123456789", &crc), 0x4B);
+}
+
+#[test]
+fn checksum_fields_accept_catalogue_names() {
+    let settings = "crc = \"CRC-32/ISO-HDLC\"";
+    let layout = format!(
+        "[settings]\nendianness = \"little\"\n{}\n{}crc = {{ type = \"u16\", checksum = \"CRC-16/MODBUS\", over = \"word\" }}\n",
+        settings, BLOCK
+    );
+    let path = common::write_layout_file("test_crc_preset_field", &layout);
+    let cfg = nvmbuilder::layout::load_layout(&path).expect("layout loads");
+    let (bytes, _) = cfg.blocks["block"]
+        .build_bytestream(None, &cfg, true, &BuildInfo::default())
+        .expect("block builds");
+
+    let modbus = &crc_catalogue::find("CRC-16/MODBUS").unwrap().crc;
+    let crc = calculate_crc(&bytes[..2], modbus) as u16;
+    assert_eq!(&bytes[2..4], &crc.to_le_bytes());
+}
+
+#[test]
+fn unknown_presets_and_missing_parameters_are_reported() {
+    let err = load("test_crc_preset_unknown", "crc = \"CRC-12/DECT\"").unwrap_err();
+    assert!(
+        err.contains("unknown CRC preset 'CRC-12/DECT'"),
+        "got: {}",
+        err
+    );
+
+    let err = load(
+        "test_crc_preset_missing",
+        "[settings.crc]\npolynomial = 0x04C11DB7\nstart = 0xFFFFFFFF",
+    )
+    .unwrap_err();
+    assert!(err.contains("need 'xor_out'"), "got: {}", err);
+}
+
+#[test]
+fn misspelled_crc_keys_are_rejected() {
+    let err = load(
+        "test_crc_preset_typo",
+        "[settings.crc]\npreset = \"CRC-32/ISO-HDLC\"\npolynomal = 0x1EDC6F41\narea = \"data\"",
+    )
+    .unwrap_err();
+    assert!(err.contains("unknown field `polynomal`"), "got: {}", err);
+}
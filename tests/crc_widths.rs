@@ -0,0 +1,144 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::layout::build_info::BuildInfo;
+use nvmbuilder::output;
+use nvmbuilder::output::checksum::calculate_crc;
+
+const LAYOUT: &str = r#"
+[settings]
+endianness = "big"
+virtual_offset = 0
+byte_swap = false
+pad_to_end = false
+
+[settings.crc]
+width = 16
+polynomial = 0x1021
+start = 0xFFFF
+xor_out = 0
+ref_in = false
+ref_out = false
+area = "data"
+
+[block.header]
+start_address = 0x80000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[block.data]
+a = { value = 0x12, type = "u8" }
+b = { value = 0x3456, type = "u16", offset = 2 }
+c = { value = 0x78, type = "u8" }
+"#;
+
+fn data_range(file_stem: &str, layout: &str) -> Result<output::DataRange, String> {
+    let path = common::write_layout_file(file_stem, layout);
+    let cfg = nvmbuilder::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let block = &cfg.blocks["block"];
+    let settings = block.effective_settings(&cfg);
+    let (bytes, padding) = block
+        .build_bytestream(None, &cfg, true, &BuildInfo::default())
+        .map_err(|e| e.to_string())?;
+    output::bytestream_to_datarange(bytes, &block.header, &settings, false, false, padding)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn crc16_slot_is_two_bytes_aligned_to_two() {
+    let dr = data_range("test_crc16_block", LAYOUT).expect("block builds");
+
+    // Five data bytes are padded to six, then the CRC follows
+    assert_eq!(dr.bytestream.len(), 6);
+    assert_eq!(dr.crc_address, 0x80006);
+    assert_eq!(dr.crc_bytestream.len(), 2);
+
+    let cfg = nvmbuilder::layout::load_layout("out/test_crc16_block.toml").unwrap();
+    let crc = calculate_crc(&dr.bytestream, &cfg.settings.crc);
+    assert_eq!(dr.crc_bytestream, (crc as u16).to_be_bytes());
+}
+
+#[test]
+fn crc8_slot_needs_no_alignment() {
+    let layout = LAYOUT
+        .replace("width = 16", "width = 8")
+        .replace("polynomial = 0x1021", "polynomial = 0x1D")
+        .replace("start = 0xFFFF", "start = 0xFF")
+        .replace("xor_out = 0\n", "xor_out = 0xFF\n");
+    let dr = data_range("test_crc8_block", &layout).expect("block builds");
+
+    assert_eq!(dr.bytestream.len(), 5);
+    assert_eq!(dr.crc_address, 0x80005);
+    assert_eq!(dr.crc_bytestream.len(), 1);
+}
+
+#[test]
+fn crc64_slot_is_eight_bytes() {
+    let layout = LAYOUT
+        .replace("width = 16", "width = 64")
+        .replace("polynomial = 0x1021", "polynomial = 0x42F0E1EBA9EA3693")
+        .replace("start = 0xFFFF", "start = 0");
+    let dr = data_range("test_crc64_block", &layout).expect("block builds");
+
+    assert_eq!(dr.crc_address, 0x80008);
+    assert_eq!(dr.crc_bytestream.len(), 8);
+}
+
+#[test]
+fn embedded_checksums_use_the_crc_width() {
+    let layout = format!(
+        "{}crc = {{ type = \"u16\", over = \"b\", checksum = {{ width = 16, polynomial = 0x1021, start = 0xFFFF, xor_out = 0, ref_in = false, ref_out = false }} }}\n",
+        LAYOUT
+    );
+    let dr = data_range("test_crc16_field", &layout).expect("block builds");
+    let cfg = nvmbuilder::layout::load_layout("out/test_crc16_field.toml").unwrap();
+    let crc = calculate_crc(&[0x34, 0x56], &cfg.settings.crc) as u16;
+    assert_eq!(&dr.bytestream[6..8], &crc.to_be_bytes());
+
+    let wrong_type = layout.replace("type = \"u16\", over", "type = \"u32\", over");
+    let err = data_range("test_crc16_field_type", &wrong_type).unwrap_err();
+    assert!(
+        err.contains("CRC16 checksums must have type u16"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn unsupported_widths_are_rejected() {
+    let layout = LAYOUT.replace("width = 16", "width = 12");
+    let err = data_range("test_crc_width_12", &layout).unwrap_err();
+    assert!(
+        err.contains("CRC width must be 8, 16, 32 or 64"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn parameters_wider_than_the_register_are_rejected() {
+    let layout = LAYOUT.replace("polynomial = 0x1021", "polynomial = 0x04C11DB7");
+    let err = data_range("test_crc_wide_polynomial", &layout).unwrap_err();
+    assert!(
+        err.contains("CRC polynomial 0x4C11DB7 does not fit in 16 bits"),
+        "got: {}",
+        err
+    );
+
+    // A block narrowing the width inherits the layout's 32-bit parameters
+    let layout = LAYOUT
+        .replace("width = 16", "width = 32")
+        .replace("polynomial = 0x1021", "polynomial = 0x04C11DB7")
+        .replace("start = 0xFFFF", "start = 0xFFFFFFFF")
+        .replace(
+            "[block.data]",
+            "[block.settings.crc]\nwidth = 16\n\n[block.data]",
+        );
+    let err = data_range("test_crc_narrowed_block", &layout).unwrap_err();
+    assert!(
+        err.contains("In field 'block'") && err.contains("does not fit in 16 bits"),
+        "got: {}",
+        err
+    );
+}
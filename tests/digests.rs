@@ -0,0 +1,185 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::commands;
+use nvmbuilder::layout::args::BlockNames;
+use nvmbuilder::layout::build_info::BuildInfo;
+use nvmbuilder::output::args::OutputFormat;
+use nvmbuilder::output::checksum::{hmac_sha256, sha256};
+
+const KEY: &[u8] = b"device-config-key";
+
+fn build(file_stem: &str, data: &str) -> Result<Vec<u8>, String> {
+    std::fs::create_dir_all("out").unwrap();
+    std::fs::write("out/test_digest.key", KEY).expect("write key file");
+    common::build_inline_block(file_stem, &common::inline_layout(data), true)
+}
+
+#[test]
+fn sha256_field_covers_named_entry() {
+    let bytes = build(
+        "test_digest_sha256",
+        r#"
+calibration.gain = { value = 0x1234, type = "u16" }
+calibration.trim = { value = -5, type = "i16" }
+calibration_hash = { type = "u8", size = 32, checksum = "sha256", over = "calibration" }
+"#,
+    )
+    .expect("sha256 field builds");
+
+    assert_eq!(&bytes[4..36], &sha256(&bytes[..4]));
+}
+
+#[test]
+fn hmac_field_reads_key_relative_to_layout() {
+    let bytes = build(
+        "test_digest_hmac",
+        r#"
+config.mode = { value = 3, type = "u32" }
+config_mac = { type = "u8", size = 32, over = "config", checksum = { algorithm = "hmac-sha256", key_file = "test_digest.key" } }
+"#,
+    )
+    .expect("hmac field builds");
+
+    assert_eq!(&bytes[4..36], &hmac_sha256(KEY, &bytes[..4]));
+}
+
+#[test]
+fn invalid_digest_fields_are_rejected() {
+    let err = build(
+        "test_digest_size",
+        r#"
+data = { value = 1, type = "u8" }
+hash = { type = "u8", size = 16, checksum = "sha256", over = "data" }
+"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("size 32"), "got: {}", err);
+
+    let err = build(
+        "test_digest_no_key",
+        r#"
+data = { value = 1, type = "u8" }
+mac = { type = "u8", size = 32, over = "data", checksum = { algorithm = "hmac-sha256" } }
+"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("requires a 'key_file'"), "got: {}", err);
+
+    let err = build(
+        "test_digest_missing_key",
+        r#"
+data = { value = 1, type = "u8" }
+mac = { type = "u8", size = 32, over = "data", checksum = { algorithm = "hmac-sha256", key_file = "nowhere.key" } }
+"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("nowhere.key"), "got: {}", err);
+}
+
+#[test]
+fn block_digest_replaces_crc() {
+    std::fs::create_dir_all("out").unwrap();
+    std::fs::write("out/test_block_digest.key", KEY).expect("write key file");
+    let layout = common::InlineLayout {
+        tables: "[block.settings.digest]\nalgorithm = \"hmac-sha256\"\nkey_file = \"test_block_digest.key\"\n",
+        ..Default::default()
+    }
+    .with_data(r#"word = { value = 0x1234, type = "u16" }"#);
+    let path = common::write_layout_file("test_block_digest", &layout);
+
+    let cfg = nvmbuilder::layout::load_layout(&path).expect("layout loads");
+    let (bytes, padding) = cfg.blocks["block"]
+        .build_bytestream(None, &cfg, true, &BuildInfo::default())
+        .expect("block builds");
+
+    let args = common::build_args(&path, "block", OutputFormat::Hex);
+    let input = BlockNames {
+        name: "block".to_string(),
+        file: path.clone(),
+    };
+    let stat = commands::generate::build_block_single(&input, None, &BuildInfo::default(), &args)
+        .expect("block with digest builds");
+
+    assert_eq!(stat.digest, Some(hmac_sha256(KEY, &bytes).to_vec()));
+    // The 32 byte digest slot counts towards the used size
+    assert_eq!(stat.used_size, bytes.len() as u32 + 32 - padding);
+}
+
+#[test]
+fn digest_slot_must_fit_in_block() {
+    let layout = common::InlineLayout {
+        length: 0x20,
+        tables: "[block.settings.digest]\nalgorithm = \"sha256\"\n",
+        ..Default::default()
+    }
+    .with_data(r#"word = { value = 1, type = "u32" }"#);
+    let path = common::write_layout_file("test_block_digest_overrun", &layout);
+
+    let args = common::build_args(&path, "block", OutputFormat::Hex);
+    let input = BlockNames {
+        name: "block".to_string(),
+        file: path.clone(),
+    };
+    let err = commands::generate::build_block_single(&input, None, &BuildInfo::default(), &args)
+        .unwrap_err();
+    assert!(err.to_string().contains("overrun"), "got: {}", err);
+}
+
+#[test]
+fn byte_swapped_digest_stats_match_in_both_commands() {
+    let layout = common::InlineLayout {
+        tables: "[block.settings]\nbyte_swap = true\n\n[block.settings.digest]\nalgorithm = \"sha256\"\n",
+        ..Default::default()
+    }
+    .with_data(r#"word = { value = 0x12345678, type = "u32" }"#);
+    let path = common::write_layout_file("test_digest_byte_swap", &layout);
+
+    let cfg = nvmbuilder::layout::load_layout(&path).expect("layout loads");
+    let (mut bytes, _) = cfg.blocks["block"]
+        .build_bytestream(None, &cfg, true, &BuildInfo::default())
+        .expect("block builds");
+    for pair in bytes.chunks_exact_mut(2) {
+        pair.swap(0, 1);
+    }
+    let expected = Some(sha256(&bytes).to_vec());
+
+    let args = common::build_args(&path, "block", OutputFormat::Hex);
+    let input = BlockNames {
+        name: "block".to_string(),
+        file: path.clone(),
+    };
+    let single = commands::generate::build_block_single(&input, None, &BuildInfo::default(), &args)
+        .expect("block builds");
+    let combined = commands::build_single_file(&args, None).expect("combined build");
+
+    assert_eq!(single.digest, expected);
+    assert_eq!(combined.block_stats[0].digest, expected);
+}
+
+#[test]
+fn block_override_turns_the_layout_digest_off() {
+    let layout = common::InlineLayout {
+        tables: "[settings.digest]\nalgorithm = \"sha256\"\n\n[block.settings]\ndigest = false\n",
+        ..Default::default()
+    }
+    .with_data(r#"word = { value = 1, type = "u32" }"#);
+    let path = common::write_layout_file("test_digest_off", &layout);
+
+    let cfg = nvmbuilder::layout::load_layout(&path).expect("layout loads");
+    assert!(cfg.settings.digest.is_some());
+    let settings = cfg.blocks["block"].effective_settings(&cfg);
+    assert!(settings.digest.is_none());
+    assert_eq!(settings.integrity_slot(), (4, 4));
+
+    let err = nvmbuilder::layout::load_layout(&common::write_layout_file(
+        "test_digest_on",
+        &layout.replace("digest = false", "digest = true"),
+    ))
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("give a digest table"),
+        "got: {}",
+        err
+    );
+}
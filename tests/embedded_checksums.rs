@@ -0,0 +1,148 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::layout::settings::{CrcArea, CrcData, CrcWidth};
+use nvmbuilder::output::checksum::calculate_crc;
+
+fn build(file_stem: &str, data: &str) -> Result<Vec<u8>, String> {
+    common::build_inline_block(file_stem, &common::inline_layout(data), true)
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    calculate_crc(
+        bytes,
+        &CrcData {
+            width: CrcWidth::W32,
+            polynomial: 0x04C11DB7,
+            start: 0xFFFFFFFF,
+            xor_out: 0xFFFFFFFF,
+            ref_in: true,
+            ref_out: true,
+            area: CrcArea::Data,
+        },
+    ) as u32
+}
+
+#[test]
+fn checksum_covers_named_branch() {
+    let bytes = build(
+        "test_embedded_checksum",
+        r#"
+device.info.serial = { value = 0x12345678, type = "u32" }
+device.info.name = { value = "nvm", type = "u8", size = 4 }
+device.info_crc = { type = "u32", checksum = "crc32", over = "device.info" }
+"#,
+    )
+    .expect("checksum builds");
+
+    assert_eq!(&bytes[8..12], &crc32(&bytes[..8]).to_le_bytes());
+}
+
+#[test]
+fn nested_checksums_are_computed_inner_first() {
+    let bytes = build(
+        "test_nested_checksum",
+        r#"
+outer.record.value = { value = 7, type = "u32" }
+outer.record_crc = { type = "u32", checksum = "crc32", over = "outer.record" }
+outer_crc = { type = "u32", over = "outer", checksum = { polynomial = 0x1EDC6F41, start = 0xFFFFFFFF, xor_out = 0xFFFFFFFF, ref_in = true, ref_out = true } }
+"#,
+    )
+    .expect("nested checksums build");
+
+    assert_eq!(&bytes[4..8], &crc32(&bytes[..4]).to_le_bytes());
+    let castagnoli = CrcData {
+        width: CrcWidth::W32,
+        polynomial: 0x1EDC6F41,
+        start: 0xFFFFFFFF,
+        xor_out: 0xFFFFFFFF,
+        ref_in: true,
+        ref_out: true,
+        area: CrcArea::Data,
+    };
+    assert_eq!(
+        &bytes[8..12],
+        &(calculate_crc(&bytes[..8], &castagnoli) as u32).to_le_bytes()
+    );
+}
+
+#[test]
+fn header_checksum_covers_payload_checksum() {
+    // The header is smaller than the payload but holds the payload's CRC
+    let bytes = build(
+        "test_header_payload_checksum",
+        r#"
+header.version = { value = 1, type = "u32" }
+header.payload_crc = { type = "u32", checksum = "crc32", over = "payload" }
+header_crc = { type = "u32", checksum = "crc32", over = "header" }
+payload.data = { value = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16], type = "u8", size = 16 }
+"#,
+    )
+    .expect("header and payload checksums build");
+
+    assert_eq!(&bytes[4..8], &crc32(&bytes[12..28]).to_le_bytes());
+    assert_eq!(&bytes[8..12], &crc32(&bytes[..8]).to_le_bytes());
+}
+
+#[test]
+fn cyclic_checksums_are_rejected() {
+    let err = build(
+        "test_cyclic_checksum",
+        r#"
+a.value = { value = 1, type = "u32" }
+a.crc_of_b = { type = "u32", checksum = "crc32", over = "b" }
+b.value = { value = 2, type = "u32" }
+b.crc_of_a = { type = "u32", checksum = "crc32", over = "a" }
+"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("Checksums depend on each other in a cycle: a.crc_of_b, b.crc_of_a"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn checksum_errors() {
+    let err = build(
+        "test_checksum_inside_region",
+        r#"
+info.value = { value = 1, type = "u32" }
+info.crc = { type = "u32", checksum = "crc32", over = "info" }
+"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("inside the region 'info'"), "got: {}", err);
+
+    let err = build(
+        "test_checksum_unknown_region",
+        r#"crc = { type = "u32", checksum = "crc32", over = "missing" }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("Unknown field 'missing'"), "got: {}", err);
+
+    let err = build(
+        "test_checksum_no_over",
+        r#"crc = { type = "u32", checksum = "crc32" }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("must be used together"), "got: {}", err);
+
+    let err = build(
+        "test_checksum_type",
+        r#"
+data = { value = 1, type = "u8" }
+crc = { type = "u16", checksum = "crc32", over = "data" }
+"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("must have type u32"), "got: {}", err);
+}
+
+#[test]
+fn layout_crc_still_requires_area() {
+    let layout = common::inline_layout("").replace("area = \"data\"\n", "");
+    let err = common::build_inline_block("test_checksum_layout_area", &layout, true).unwrap_err();
+    assert!(err.contains("need 'area'"), "got: {}", err);
+}
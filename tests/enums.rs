@@ -0,0 +1,54 @@
+#[path = "common/mod.rs"]
+mod common;
+
+const ENUMS: &str = r#"
+[enums.BaudRate]
+B9600 = 0
+B115200 = 1
+B921600 = 7
+"#;
+
+fn layout(data: &str) -> String {
+    common::InlineLayout {
+        tables: ENUMS,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+#[test]
+fn enum_names_resolve_in_scalars_arrays_and_bitfields() {
+    let layout = layout(
+        r#"
+uart.baud = { value = "B115200", type = "u8", enum = "BaudRate" }
+uart.numeric = { value = 7, type = "u8", enum = "BaudRate" }
+uart.fallbacks = { value = ["B9600", "B921600"], type = "u16", size = 2, enum = "BaudRate" }
+uart.cfg = { type = "u8", bitfield = { baud = { bits = 3, value = "B921600", enum = "BaudRate" }, parity = { bits = 1, value = 1 } } }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_enum_resolve", &layout, true)
+        .expect("enum values should resolve");
+
+    assert_eq!(&bytes[..7], &[1, 7, 0, 0, 7, 0, 0x0F]);
+}
+
+#[test]
+fn unknown_enum_member_lists_allowed_names() {
+    let layout = layout(r#"uart.baud = { value = "B19200", type = "u8", enum = "BaudRate" }"#);
+
+    let err = common::build_inline_block("test_enum_unknown_member", &layout, false).unwrap_err();
+    assert!(
+        err.contains("Allowed names: B9600, B115200, B921600"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn undefined_enum_is_rejected() {
+    let layout = layout(r#"uart.parity = { value = "Even", type = "u8", enum = "Parity" }"#);
+
+    let err = common::build_inline_block("test_enum_undefined", &layout, false).unwrap_err();
+    assert!(err.contains("Enum 'Parity' is not defined"), "got: {}", err);
+}
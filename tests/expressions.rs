@@ -0,0 +1,97 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::layout::build_info::BuildInfo;
+
+const CONSTANTS: &str = r#"
+[constants]
+FW_MAJOR = 3
+FW_MINOR = 14
+GAIN = 0.5
+"#;
+
+fn layout(data: &str) -> String {
+    common::InlineLayout {
+        endianness: "big",
+        tables: CONSTANTS,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+fn build(file_stem: &str, data: &str, strict: bool) -> Result<Vec<u8>, String> {
+    common::build_inline_block(file_stem, &layout(data), strict)
+}
+
+#[test]
+fn literal_expressions_use_constants() {
+    let bytes = build(
+        "test_expr_constants",
+        r#"
+flags = { value = "(1 << 12) | 0x3", type = "u16" }
+version = { value = "FW_MAJOR * 256 + FW_MINOR", type = "u16" }
+limits = { value = ["-FW_MAJOR", "FW_MINOR % 4"], type = "i8", size = 2 }
+cfg = { type = "u8", bitfield = { major = { bits = 4, value = "FW_MAJOR + 1" }, minor = { bits = 4, value = 2 } } }
+"#,
+        true,
+    )
+    .expect("expressions evaluate");
+
+    assert_eq!(&bytes[..7], &[0x10, 0x03, 0x03, 0x0E, 0xFD, 0x02, 0x24]);
+}
+
+#[test]
+fn expression_results_go_through_strict_conversion() {
+    let data = r#"gain = { value = "GAIN * 3", type = "u8" }"#;
+
+    let err = build("test_expr_strict", data, true).unwrap_err();
+    assert!(err.contains("exact integer"), "got: {}", err);
+
+    let bytes = build("test_expr_lenient", data, false).expect("lenient truncates");
+    assert_eq!(bytes[0], 1);
+}
+
+#[test]
+fn strings_stay_strings_for_enums_and_arrays() {
+    let bytes = build(
+        "test_expr_strings",
+        r#"name = { value = "FW_MAJOR", type = "u8", size = 8 }"#,
+        true,
+    )
+    .expect("string literal builds");
+    assert_eq!(&bytes[..8], b"FW_MAJOR");
+}
+
+#[test]
+fn excel_names_resolve_in_expressions() {
+    let Some(ds) = common::find_working_datasheet() else {
+        return;
+    };
+    let layout =
+        layout(r#"version = { value = "FWVersionMajor * 256 + FWVersionMinor", type = "u16" }"#);
+    let path = common::write_layout_file("test_expr_excel", &layout);
+    let cfg = nvmbuilder::layout::load_layout(&path).expect("layout loads");
+    let (bytes, _) = cfg.blocks["block"]
+        .build_bytestream(Some(&ds), &cfg, false, &BuildInfo::default())
+        .expect("block builds");
+
+    let major = ds.retrieve_single_value("FWVersionMajor").unwrap();
+    let minor = ds.retrieve_single_value("FWVersionMinor").unwrap();
+    let expected = (major.as_f64().unwrap() * 256.0 + minor.as_f64().unwrap()) as u16;
+    assert_eq!(&bytes[..2], &expected.to_be_bytes());
+}
+
+#[test]
+fn invalid_expressions_are_reported() {
+    let err = build(
+        "test_expr_invalid",
+        r#"x = { value = "FW_MAJOR +", type = "u8" }"#,
+        false,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("Invalid expression 'FW_MAJOR +'"),
+        "got: {}",
+        err
+    );
+}
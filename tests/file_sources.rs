@@ -0,0 +1,90 @@
+#[path = "common/mod.rs"]
+mod common;
+
+const CERT: &[u8] = &[0x30, 0x82, 0x01, 0x0A, 0x02, 0x82, 0x01, 0x01];
+
+/// Writes the test certificate next to the layout and builds the given data.
+fn build(file_stem: &str, data: &str) -> Result<Vec<u8>, String> {
+    std::fs::create_dir_all("out/certs").unwrap();
+    std::fs::write("out/certs/device.der", CERT).expect("write certificate");
+    common::build_inline_block(file_stem, &common::inline_layout(data), true)
+}
+
+#[test]
+fn file_contents_are_padded_to_size() {
+    let bytes = build(
+        "test_file_source",
+        r#"
+cert = { file = "certs/device.der", type = "u8", size = 10 }
+exact = { file = "certs/device.der", type = "u8", SIZE = 8 }
+"#,
+    )
+    .expect("file sources build");
+
+    assert_eq!(&bytes[..8], CERT);
+    assert_eq!(&bytes[8..10], &[0xFF, 0xFF]);
+    assert_eq!(&bytes[10..18], CERT);
+}
+
+#[test]
+fn file_byte_range_is_inserted() {
+    let bytes = build(
+        "test_file_range",
+        r#"
+head = { file = "certs/device.der", type = "u8", SIZE = 2, file_length = 2 }
+tail = { file = "certs/device.der", type = "u8", SIZE = 4, file_offset = 4 }
+"#,
+    )
+    .expect("file ranges build");
+
+    assert_eq!(&bytes[..2], &CERT[..2]);
+    assert_eq!(&bytes[2..6], &CERT[4..]);
+}
+
+#[test]
+fn file_size_rules_are_enforced() {
+    let err = build(
+        "test_file_too_large",
+        r#"cert = { file = "certs/device.der", type = "u8", size = 4 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("larger than defined size"), "got: {}", err);
+
+    let err = build(
+        "test_file_too_small",
+        r#"cert = { file = "certs/device.der", type = "u8", SIZE = 16 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("strict SIZE"), "got: {}", err);
+
+    let err = build(
+        "test_file_range_outside",
+        r#"cert = { file = "certs/device.der", type = "u8", size = 16, file_offset = 6, file_length = 4 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("6..10 is outside"), "got: {}", err);
+}
+
+#[test]
+fn invalid_file_sources_are_rejected() {
+    let err = build(
+        "test_file_missing",
+        r#"cert = { file = "certs/missing.der", type = "u8", size = 16 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("missing.der"), "got: {}", err);
+
+    let err = build(
+        "test_file_type",
+        r#"cert = { file = "certs/device.der", type = "u16", size = 4 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("should have type u8"), "got: {}", err);
+
+    let err = build(
+        "test_file_offset_without_file",
+        r#"x = { value = 1, type = "u8", file_offset = 2 }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("only apply to 'file' sources"), "got: {}", err);
+}
@@ -0,0 +1,79 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn q_formats_scale_by_fractional_bits() {
+    let layout = common::inline_layout(
+        r#"
+coeff.half = { value = 0.5, type = "q15" }
+coeff.neg_one = { value = -1.0, type = "q15" }
+coeff.gain = { value = 1.5, type = "uq16.16" }
+coeff.taps = { value = [0.25, -0.5], type = "q7", size = 2 }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_fixed_scale", &layout, true)
+        .expect("fixed-point values should build");
+
+    assert_eq!(
+        &bytes[..10],
+        &[0x00, 0x40, 0x00, 0x80, 0x00, 0x80, 0x01, 0x00, 0x20, 0xC0]
+    );
+}
+
+#[test]
+fn q_format_saturates_unless_strict() {
+    let layout = common::inline_layout(r#"coeff.one = { value = 1.0, type = "q15" }"#);
+
+    let bytes = common::build_inline_block("test_fixed_saturate", &layout, false)
+        .expect("lenient mode saturates");
+    assert_eq!(&bytes[..2], &[0xFF, 0x7F]);
+
+    let err = common::build_inline_block("test_fixed_overflow", &layout, true).unwrap_err();
+    assert!(err.contains("out of range for fixed-point"), "got: {}", err);
+}
+
+#[test]
+fn q_format_strict_rejects_64_bit_upper_bound() {
+    for (stem, data) in [
+        (
+            "test_fixed_q63",
+            r#"coeff.one = { value = 1.0, type = "q63" }"#,
+        ),
+        (
+            "test_fixed_uq64",
+            r#"coeff.one = { value = 1.0, type = "uq64" }"#,
+        ),
+        (
+            "test_fixed_uq1_63",
+            r#"coeff.two = { value = 2.0, type = "uq1.63" }"#,
+        ),
+    ] {
+        let layout = common::inline_layout(data);
+        let err = common::build_inline_block(stem, &layout, true).unwrap_err();
+        assert!(
+            err.contains("out of range for fixed-point"),
+            "{}: got: {}",
+            stem,
+            err
+        );
+    }
+}
+
+#[test]
+fn q_format_strict_rejects_precision_loss() {
+    let layout = common::inline_layout(r#"coeff.tenth = { value = 0.1, type = "q15" }"#);
+
+    let err = common::build_inline_block("test_fixed_precision", &layout, true).unwrap_err();
+    assert!(err.contains("not exactly representable"), "got: {}", err);
+}
+
+#[test]
+fn q_format_rejects_unsupported_width() {
+    let layout = common::inline_layout(r#"coeff.odd = { value = 0.5, type = "q3.3" }"#);
+
+    assert!(common::build_inline_block("test_fixed_width", &layout, false).is_err());
+
+    let layout = common::inline_layout(r#"coeff.huge = { value = 0.5, type = "q4294967295.1" }"#);
+    assert!(common::build_inline_block("test_fixed_width_overflow", &layout, false).is_err());
+}
@@ -0,0 +1,104 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn build_layout(
+    file_stem: &str,
+    endianness: &str,
+    data: &str,
+    strict: bool,
+) -> Result<Vec<u8>, String> {
+    let layout_toml = common::InlineLayout {
+        endianness,
+        ..Default::default()
+    }
+    .with_data(data);
+
+    common::build_inline_block(file_stem, &layout_toml, strict)
+}
+
+#[test]
+fn half_floats_follow_endianness() {
+    let data = r#"
+a = { value = 1.0, type = "f16" }
+b = { value = -2.5, type = "bf16" }
+c = { value = [0.5, 65504.0], type = "f16", size = 2 }
+"#;
+
+    let little = build_layout("test_half_le", "little", data, true).expect("builds");
+    assert_eq!(
+        &little[..8],
+        &[0x00, 0x3C, 0x20, 0xC0, 0x00, 0x38, 0xFF, 0x7B]
+    );
+
+    let big = build_layout("test_half_be", "big", data, true).expect("builds");
+    assert_eq!(&big[..8], &[0x3C, 0x00, 0xC0, 0x20, 0x38, 0x00, 0x7B, 0xFF]);
+}
+
+#[test]
+fn values_that_round_are_accepted_in_strict_mode() {
+    // 0.1 is not exact in either format but stays within the rounding error
+    let bytes = build_layout(
+        "test_half_rounding",
+        "little",
+        r#"
+a = { value = 0.1, type = "f16" }
+b = { value = 0.1, type = "bf16" }
+"#,
+        true,
+    )
+    .expect("rounded values build");
+    assert_eq!(&bytes[..4], &[0x66, 0x2E, 0xCD, 0x3D]);
+}
+
+#[test]
+fn strict_mode_rejects_overflow_and_precision_loss() {
+    let err = build_layout(
+        "test_half_overflow",
+        "little",
+        r#"a = { value = 70000.0, type = "f16" }"#,
+        true,
+    )
+    .unwrap_err();
+    assert!(err.contains("out of range for f16"), "got: {}", err);
+
+    // Subnormal in f16, so far fewer significant bits survive
+    let err = build_layout(
+        "test_half_subnormal",
+        "little",
+        r#"a = { value = 1.0e-7, type = "f16" }"#,
+        true,
+    )
+    .unwrap_err();
+    assert!(err.contains("relative tolerance"), "got: {}", err);
+
+    let bytes = build_layout(
+        "test_half_tolerance",
+        "little",
+        r#"a = { value = 1.0e-7, type = "f16", tolerance = 0.5 }"#,
+        true,
+    )
+    .expect("a looser tolerance accepts the value");
+    assert_eq!(&bytes[..2], &[0x02, 0x00]);
+
+    // Lenient mode stores the overflow as infinity
+    let bytes = build_layout(
+        "test_half_lenient",
+        "little",
+        r#"a = { value = 70000.0, type = "f16" }"#,
+        false,
+    )
+    .expect("lenient mode builds");
+    assert_eq!(&bytes[..2], &[0x00, 0x7C]);
+}
+
+#[test]
+fn tolerance_requires_a_half_float_type() {
+    let err = build_layout(
+        "test_half_tolerance_type",
+        "little",
+        r#"a = { value = 1.0, type = "f32", tolerance = 0.1 }"#,
+        true,
+    )
+    .unwrap_err();
+    assert!(err.contains("'tolerance' only applies"), "got: {}", err);
+}
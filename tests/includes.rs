@@ -0,0 +1,286 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use std::path::PathBuf;
+
+use nvmbuilder::layout::build_info::BuildInfo;
+
+const COMMON_SETTINGS: &str = r#"
+[settings]
+endianness = "little"
+virtual_offset = 0
+byte_swap = false
+pad_to_end = false
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[types.Version]
+major = { value = 1, type = "u8" }
+minor = { value = 2, type = "u8" }
+"#;
+
+/// Writes the given files into a fresh directory under out/ and returns its path.
+fn write_files(dir: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = PathBuf::from("out").join(dir);
+    let _ = std::fs::remove_dir_all(&dir);
+    for (name, contents) in files {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("create include dir");
+        std::fs::write(path, contents).expect("write include file");
+    }
+    dir
+}
+
+fn load(path: PathBuf) -> Result<nvmbuilder::layout::block::Config, String> {
+    nvmbuilder::layout::load_layout(path.to_str().unwrap()).map_err(|e| e.to_string())
+}
+
+#[test]
+fn included_settings_types_and_blocks_are_merged() {
+    let dir = write_files(
+        "include_merge",
+        &[
+            ("shared/common.toml", COMMON_SETTINGS),
+            (
+                "shared/calibration.yaml",
+                "calibration:\n  header:\n    start_address: 0x90000\n    length: 0x20\n    crc_location: end\n    padding: 0xFF\n  data:\n    gain: { value: 3, type: u16 }\n",
+            ),
+            (
+                "main.toml",
+                r#"
+include = ["shared/common.toml", "shared/calibration.yaml"]
+
+[settings]
+endianness = "big"
+
+[block.header]
+start_address = 0x80000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[block.data]
+version = { type = "Version" }
+gain = { value = 0x1234, type = "u16" }
+"#,
+            ),
+        ],
+    );
+
+    let cfg = load(dir.join("main.toml")).expect("layout with includes loads");
+    assert_eq!(cfg.blocks.len(), 2);
+    assert!(cfg.blocks.contains_key("calibration"));
+
+    let (bytes, _) = cfg.blocks["block"]
+        .build_bytestream(None, &cfg, true, &BuildInfo::default())
+        .expect("block builds");
+    // The including file overrides the endianness, the CRC settings come from the include.
+    assert_eq!(&bytes[..4], &[1, 2, 0x12, 0x34]);
+    assert_eq!(cfg.settings.crc.polynomial, 0x04C11DB7);
+}
+
+#[test]
+fn file_sources_resolve_against_the_declaring_file() {
+    let dir = write_files(
+        "include_file_source",
+        &[
+            ("shared/common.toml", COMMON_SETTINGS),
+            ("shared/blob.bin", "SHARED"),
+            ("blob.bin", "ROOT"),
+            (
+                "shared/calibration.toml",
+                r#"
+[types.Blob]
+bytes = { file = "blob.bin", type = "u8", SIZE = 6 }
+
+[calibration.header]
+start_address = 0x90000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[calibration.data]
+blob = { file = "blob.bin", type = "u8", SIZE = 6 }
+"#,
+            ),
+            (
+                "main.toml",
+                r#"
+include = ["shared/common.toml", "shared/calibration.toml"]
+
+[block.header]
+start_address = 0x80000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[block.data]
+shared = { type = "Blob" }
+root = { file = "blob.bin", type = "u8", SIZE = 4 }
+"#,
+            ),
+        ],
+    );
+
+    let cfg = load(dir.join("main.toml")).expect("layout with includes loads");
+    let build = |name: &str| {
+        cfg.blocks[name]
+            .build_bytestream(None, &cfg, true, &BuildInfo::default())
+            .expect("block builds")
+            .0
+    };
+    assert_eq!(&build("calibration")[..6], b"SHARED");
+    assert_eq!(&build("block")[..10], b"SHAREDROOT");
+}
+
+#[test]
+fn key_files_resolve_against_the_declaring_file() {
+    let dir = write_files(
+        "include_key_file",
+        &[
+            ("shared/device.key", "shared-key"),
+            (
+                "shared/common.toml",
+                &format!(
+                    "{}\n[settings.digest]\nalgorithm = \"hmac-sha256\"\nkey_file = \"device.key\"\n",
+                    COMMON_SETTINGS
+                ),
+            ),
+            (
+                "main.toml",
+                r#"
+include = ["shared/common.toml"]
+
+[block.header]
+start_address = 0x80000
+length = 0x40
+crc_location = "end"
+padding = 0xFF
+
+[block.data]
+word = { value = 1, type = "u32" }
+"#,
+            ),
+        ],
+    );
+
+    let cfg = load(dir.join("main.toml")).expect("layout with includes loads");
+    let digest = cfg.blocks["block"].effective_settings(&cfg).digest;
+    assert_eq!(
+        digest.expect("digest set").key().expect("key reads"),
+        b"shared-key"
+    );
+}
+
+#[test]
+fn duplicate_block_names_point_at_both_files() {
+    let block = r#"
+[block.header]
+start_address = 0x80000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[block.data]
+x = { value = 1, type = "u8" }
+"#;
+    let dir = write_files(
+        "include_duplicate",
+        &[
+            ("common.toml", COMMON_SETTINGS),
+            ("other.toml", block),
+            (
+                "main.toml",
+                &format!("include = [\"common.toml\", \"other.toml\"]\n{}", block),
+            ),
+        ],
+    );
+
+    let err = load(dir.join("main.toml")).unwrap_err();
+    assert!(err.contains("duplicate block 'block'"), "got: {}", err);
+    assert!(
+        err.contains("other.toml") && err.contains("main.toml"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn include_cycles_are_rejected() {
+    let dir = write_files(
+        "include_cycle",
+        &[
+            ("a.toml", "include = [\"b.json\"]\n"),
+            ("b.json", r#"{ "include": ["a.toml"] }"#),
+        ],
+    );
+
+    let err = load(dir.join("a.toml")).unwrap_err();
+    assert!(err.contains("include cycle"), "got: {}", err);
+    assert!(
+        err.contains("a.toml -> ") && err.contains("b.json"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn missing_include_is_reported() {
+    let dir = write_files(
+        "include_missing",
+        &[("main.toml", "include = [\"nowhere.toml\"]\n")],
+    );
+
+    let err = load(dir.join("main.toml")).unwrap_err();
+    assert!(err.contains("nowhere.toml"), "got: {}", err);
+}
+
+#[test]
+fn input_hash_covers_included_layouts_and_file_sources() {
+    let files = |common: &str, blob: &str| {
+        write_files(
+            "include_input_hash",
+            &[
+                ("shared/common.toml", common),
+                ("blob.bin", blob),
+                (
+                    "main.toml",
+                    r#"
+include = ["shared/common.toml"]
+
+[block.header]
+start_address = 0x80000
+length = 0x20
+crc_location = "end"
+padding = 0xFF
+
+[block.data]
+hash = { type = "u32", build = "input_hash" }
+blob = { file = "blob.bin", type = "u8", SIZE = 4 }
+"#,
+                ),
+            ],
+        )
+    };
+    let hash = |dir: PathBuf| {
+        let path = dir.join("main.toml");
+        let args = common::build_args(
+            path.to_str().unwrap(),
+            "block",
+            nvmbuilder::output::args::OutputFormat::Hex,
+        );
+        BuildInfo::from_args(&args).expect("build info").input_hash
+    };
+
+    let base = hash(files(COMMON_SETTINGS, "ROOT"));
+    assert_eq!(base, hash(files(COMMON_SETTINGS, "ROOT")));
+    let edited_include = COMMON_SETTINGS.replace("minor = { value = 2", "minor = { value = 3");
+    assert_ne!(base, hash(files(&edited_include, "ROOT")));
+    assert_ne!(base, hash(files(COMMON_SETTINGS, "TOOR")));
+}
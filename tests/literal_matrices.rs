@@ -0,0 +1,44 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn literal_2d_array_is_emitted_row_major() {
+    let layout = common::inline_layout(
+        r#"rotation = { value = [[1, 0, 0], [0, 1, 0], [0, 0, 1]], type = "i8", SIZE = [3, 3] }"#,
+    );
+
+    let bytes = common::build_inline_block("test_matrix_identity", &layout, true)
+        .expect("literal matrix should build");
+    assert_eq!(&bytes[..9], &[1, 0, 0, 0, 1, 0, 0, 0, 1]);
+}
+
+#[test]
+fn lowercase_size_pads_missing_rows() {
+    let layout = common::inline_layout(
+        r#"table = { value = [[1, 2], [3, 4]], type = "u8", size = [3, 2] }"#,
+    );
+
+    let bytes = common::build_inline_block("test_matrix_pad", &layout, false)
+        .expect("lowercase size allows padding");
+    assert_eq!(&bytes[..6], &[1, 2, 3, 4, 0xFF, 0xFF]);
+}
+
+#[test]
+fn literal_2d_array_size_checks() {
+    let underfilled = common::inline_layout(
+        r#"table = { value = [[1, 2], [3, 4]], type = "u8", SIZE = [3, 2] }"#,
+    );
+    let err = common::build_inline_block("test_matrix_strict", &underfilled, false).unwrap_err();
+    assert!(err.contains("smaller than defined size"), "got: {}", err);
+
+    let ragged =
+        common::inline_layout(r#"table = { value = [[1, 2], [3]], type = "u8", size = [2, 2] }"#);
+    let err = common::build_inline_block("test_matrix_ragged", &ragged, false).unwrap_err();
+    assert!(err.contains("column count mismatch"), "got: {}", err);
+
+    let too_many = common::inline_layout(
+        r#"table = { value = [[1, 2], [3, 4], [5, 6]], type = "u8", size = [2, 2] }"#,
+    );
+    let err = common::build_inline_block("test_matrix_rows", &too_many, false).unwrap_err();
+    assert!(err.contains("greater than defined size"), "got: {}", err);
+}
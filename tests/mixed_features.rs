@@ -1,5 +1,6 @@
 use nvmbuilder::commands::generate::build_block_single;
 use nvmbuilder::layout::args::BlockNames;
+use nvmbuilder::layout::build_info::BuildInfo;
 use nvmbuilder::output::args::{OutputArgs, OutputFormat};
 
 #[path = "common/mod.rs"]
@@ -93,6 +94,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: be_path.clone(),
             }],
             strict: false,
+            build_number: None,
         },
         variant: var_args.clone(),
         output: OutputArgs {
@@ -112,6 +114,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
             file: be_path.clone(),
         },
         ds.as_ref(),
+        &BuildInfo::default(),
         &args_be_hex,
     )
     .expect("be-hex");
@@ -125,6 +128,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: be_path.clone(),
             }],
             strict: false,
+            build_number: None,
         },
         variant: var_args.clone(),
         output: OutputArgs {
@@ -144,6 +148,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
             file: be_path.clone(),
         },
         ds.as_ref(),
+        &BuildInfo::default(),
         &args_be_mot,
     )
     .expect("be-mot");
@@ -157,6 +162,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: le_path.clone(),
             }],
             strict: true, // exercise strict path on numeric arrays
+            build_number: None,
         },
         variant: var_args.clone(),
         output: OutputArgs {
@@ -176,6 +182,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
             file: le_path.clone(),
         },
         ds.as_ref(),
+        &BuildInfo::default(),
         &args_le_hex,
     )
     .expect("le-hex");
@@ -189,6 +196,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: le_path.clone(),
             }],
             strict: true,
+            build_number: None,
         },
         variant: var_args,
         output: OutputArgs {
@@ -208,6 +216,7 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
             file: le_path.clone(),
         },
         ds.as_ref(),
+        &BuildInfo::default(),
         &args_le_mot,
     )
     .expect("le-mot");
@@ -1,4 +1,5 @@
 use nvmbuilder::commands;
+use nvmbuilder::layout::build_info::BuildInfo;
 use nvmbuilder::variant::DataSheet;
 
 #[path = "common/mod.rs"]
@@ -20,6 +21,7 @@ fn test_build_without_excel() {
         layout: nvmbuilder::layout::args::LayoutArgs {
             blocks: vec![input.clone()],
             strict: false,
+            build_number: None,
         },
         variant: nvmbuilder::variant::args::VariantArgs {
             xlsx: None,
@@ -40,7 +42,7 @@ fn test_build_without_excel() {
     };
 
     // This should succeed since all values are inline
-    commands::generate::build_block_single(&input, None, &args)
+    commands::generate::build_block_single(&input, None, &BuildInfo::default(), &args)
         .expect("build should succeed without Excel file");
 
     common::assert_out_file_exists_custom(
@@ -67,6 +69,7 @@ fn test_error_when_name_without_excel() {
         layout: nvmbuilder::layout::args::LayoutArgs {
             blocks: vec![input.clone()],
             strict: false,
+            build_number: None,
         },
         variant: nvmbuilder::variant::args::VariantArgs {
             xlsx: None,
@@ -87,7 +90,7 @@ fn test_error_when_name_without_excel() {
     };
 
     // This should fail with MissingDataSheet error
-    let result = commands::generate::build_block_single(&input, None, &args);
+    let result = commands::generate::build_block_single(&input, None, &BuildInfo::default(), &args);
     assert!(
         result.is_err(),
         "Expected error when using 'name' without Excel file"
@@ -0,0 +1,99 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn build_layout(
+    file_stem: &str,
+    endianness: &str,
+    data: &str,
+    strict: bool,
+) -> Result<Vec<u8>, String> {
+    let layout_toml = common::InlineLayout {
+        endianness,
+        ..Default::default()
+    }
+    .with_data(data);
+
+    common::build_inline_block(file_stem, &layout_toml, strict)
+}
+
+#[test]
+fn odd_width_integers_emit_their_byte_width() {
+    let data = r#"
+a = { value = 0x123456, type = "u24" }
+b = { value = -2, type = "i24" }
+c = { value = 0x0102030405, type = "u40" }
+d = { value = [1, 0xABCDEF], type = "u24", size = 2 }
+"#;
+
+    let little = build_layout("test_odd_int_le", "little", data, true).expect("builds");
+    assert_eq!(&little[..3], &[0x56, 0x34, 0x12]);
+    assert_eq!(&little[3..6], &[0xFE, 0xFF, 0xFF]);
+    assert_eq!(&little[6..11], &[0x05, 0x04, 0x03, 0x02, 0x01]);
+    assert_eq!(&little[11..17], &[0x01, 0x00, 0x00, 0xEF, 0xCD, 0xAB]);
+
+    let big = build_layout("test_odd_int_be", "big", data, true).expect("builds");
+    assert_eq!(&big[..3], &[0x12, 0x34, 0x56]);
+    assert_eq!(&big[3..6], &[0xFF, 0xFF, 0xFE]);
+}
+
+#[test]
+fn odd_width_integers_align_to_power_of_two_factor() {
+    let data = r#"
+a = { value = 1, type = "u8" }
+b = { value = 2, type = "u24" }
+c = { value = 3, type = "u48" }
+"#;
+
+    let bytes = build_layout("test_odd_int_align", "little", data, true).expect("builds");
+    // u24 is byte aligned, u48 is aligned to 2 bytes
+    assert_eq!(&bytes[..4], &[1, 2, 0, 0]);
+    assert_eq!(&bytes[4..10], &[3, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn odd_width_range_checks() {
+    let data = r#"a = { value = 0x1000000, type = "u24" }"#;
+    let err = build_layout("test_odd_int_range_strict", "little", data, true).unwrap_err();
+    assert!(err.contains("out of range for u24"), "got: {}", err);
+
+    // Lenient mode wraps integers to the width, like the native types
+    let bytes = build_layout("test_odd_int_range_lenient", "little", data, false).expect("builds");
+    assert_eq!(&bytes[..3], &[0x00, 0x00, 0x00]);
+
+    let data = r#"a = { value = -8388609, type = "i24" }"#;
+    let err = build_layout("test_odd_int_signed_strict", "little", data, true).unwrap_err();
+    assert!(err.contains("out of range for i24"), "got: {}", err);
+    let bytes = build_layout("test_odd_int_signed_lenient", "little", data, false).expect("builds");
+    assert_eq!(&bytes[..3], &[0xFF, 0xFF, 0x7F]);
+}
+
+#[test]
+fn lenient_odd_widths_match_native_casts() {
+    let data = r#"
+wrap24 = { value = 0x1000001, type = "u24" }
+wrap8 = { value = 0x101, type = "u8" }
+sat24 = { value = 1e12, type = "u24" }
+sat8 = { value = 1e12, type = "u8" }
+neg24 = { value = -1.0, type = "u24" }
+"#;
+    let bytes = build_layout("test_odd_int_lenient_casts", "little", data, false).expect("builds");
+
+    assert_eq!(&bytes[..3], &[0x01, 0x00, 0x00]);
+    assert_eq!(bytes[3], 0x01);
+    assert_eq!(&bytes[4..7], &[0xFF, 0xFF, 0xFF]);
+    assert_eq!(bytes[7], 0xFF);
+    assert_eq!(&bytes[8..11], &[0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn non_byte_integer_widths_are_rejected() {
+    assert!(
+        build_layout(
+            "test_odd_int_width",
+            "little",
+            r#"a = { value = 1, type = "u12" }"#,
+            false,
+        )
+        .is_err()
+    );
+}
@@ -0,0 +1,60 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout(data: &str) -> String {
+    common::InlineLayout {
+        padding: 0xEE,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+#[test]
+fn leaf_and_branch_offsets_seek_with_padding() {
+    let layout = layout(
+        r#"
+header.magic = { value = 0xA5, type = "u8" }
+header.version = { value = 0x0102, type = "u16", offset = 3 }
+legacy._offset = 0x8
+legacy.id = { value = 0x11, type = "u8" }
+legacy.flags = { value = 0x22, type = "u8" }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_offsets_seek", &layout, true)
+        .expect("explicit offsets should build");
+
+    assert_eq!(
+        &bytes[..10],
+        &[0xA5, 0xEE, 0xEE, 0x02, 0x01, 0xEE, 0xEE, 0xEE, 0x11, 0x22]
+    );
+}
+
+#[test]
+fn offset_behind_write_position_is_rejected() {
+    let layout = layout(
+        r#"
+header.magic = { value = 0xDEADBEEF, type = "u32" }
+header.version = { value = 1, type = "u16", offset = 2 }
+"#,
+    );
+
+    let err = common::build_inline_block("test_offsets_overlap", &layout, false).unwrap_err();
+    assert!(err.contains("In field 'version'"), "got: {}", err);
+    assert!(err.contains("fields would overlap"), "got: {}", err);
+}
+
+#[test]
+fn child_named_offset_is_a_regular_field() {
+    let layout = layout(
+        r#"
+calibration.gain = { value = 0x11, type = "u8" }
+calibration.offset = { value = -2, type = "i8" }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_offsets_child_name", &layout, true)
+        .expect("a field named offset should build");
+
+    assert_eq!(&bytes[..2], &[0x11, 0xFE]);
+}
@@ -0,0 +1,65 @@
+#[path = "common/mod.rs"]
+mod common;
+
+const ENUMS: &str = r#"
+[enums.Mode]
+Off = 0
+Eco = 1
+Boost = 2
+"#;
+
+fn layout(data: &str) -> String {
+    common::InlineLayout {
+        tables: ENUMS,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+#[test]
+fn values_within_bounds_build() {
+    let layout = layout(
+        r#"
+limits.temp = { value = 85, type = "i16", min = -40, max = 125 }
+limits.table = { value = [1, 2, 3, 4], type = "u8", size = 4, min = 0, max = 10 }
+mode = { value = "Eco", type = "u8", enum = "Mode", allowed = ["Off", "Eco"] }
+"#,
+    );
+
+    common::build_inline_block("test_range_ok", &layout, true).expect("values are in range");
+}
+
+#[test]
+fn value_above_max_names_field_value_and_bound() {
+    let layout = layout(r#"limits.temp = { value = 130, type = "i16", min = -40, max = 125 }"#);
+
+    let err = common::build_inline_block("test_range_max", &layout, false).unwrap_err();
+    assert!(err.contains("In field 'temp'"), "got: {}", err);
+    assert!(
+        err.contains("value 130 is above maximum 125"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn array_element_below_min_is_rejected() {
+    let layout = layout(r#"limits.table = { value = [5, -1, 7], type = "i8", size = 3, min = 0 }"#);
+
+    let err = common::build_inline_block("test_range_min", &layout, false).unwrap_err();
+    assert!(err.contains("value -1 is below minimum 0"), "got: {}", err);
+}
+
+#[test]
+fn value_outside_allowed_set_is_rejected() {
+    let layout = layout(
+        r#"mode = { value = "Boost", type = "u8", enum = "Mode", allowed = ["Off", "Eco"] }"#,
+    );
+
+    let err = common::build_inline_block("test_range_allowed", &layout, false).unwrap_err();
+    assert!(
+        err.contains("value 2 is not one of the allowed values: 'Off', 'Eco'"),
+        "got: {}",
+        err
+    );
+}
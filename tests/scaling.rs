@@ -0,0 +1,38 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn scaling_converts_physical_to_raw() {
+    let layout = common::inline_layout(
+        r#"
+temp.limit = { value = 85.0, type = "u8", scaling = { scale = 0.5, offset = -40.0 } }
+volts.table = { value = [3.3, 5.0], type = "u16", size = 2, scaling = { scale = 0.01, rounding = "nearest" } }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_scaling_raw", &layout, true)
+        .expect("scaled values should build");
+
+    // (85 + 40) / 0.5 = 250; 3.3 / 0.01 = 330; 5.0 / 0.01 = 500
+    assert_eq!(&bytes[..6], &[250, 0xFF, 0x4A, 0x01, 0xF4, 0x01]);
+}
+
+#[test]
+fn strict_rejects_inexact_scaled_value_without_rounding() {
+    let layout = common::inline_layout(
+        r#"temp.limit = { value = 85.3, type = "u8", scaling = { scale = 0.5, offset = -40.0 } }"#,
+    );
+
+    let err = common::build_inline_block("test_scaling_inexact", &layout, true).unwrap_err();
+    assert!(err.contains("exact integer"), "got: {}", err);
+}
+
+#[test]
+fn strict_rejects_out_of_range_scaled_value() {
+    let layout = common::inline_layout(
+        r#"temp.limit = { value = 100.0, type = "u8", scaling = { scale = 0.5, offset = -40.0, rounding = "nearest" } }"#,
+    );
+
+    let err = common::build_inline_block("test_scaling_range", &layout, true).unwrap_err();
+    assert!(err.contains("out of range for u8"), "got: {}", err);
+}
@@ -1,5 +1,7 @@
 use std::io::Write;
 
+use nvmbuilder::layout::build_info::BuildInfo;
+
 #[path = "common/mod.rs"]
 mod common;
 
@@ -40,7 +42,7 @@ short_array = { value = [1, 2, 3], type = "u16", size = 10 }
     let block = cfg.blocks.get("block").expect("block present");
 
     let (bytes, _padding) = block
-        .build_bytestream(None, &cfg.settings, false)
+        .build_bytestream(None, &cfg, false, &BuildInfo::default())
         .expect("lowercase size should allow padding");
 
     assert!(bytes.len() >= 20);
@@ -82,7 +84,7 @@ short_array = { value = [1, 2, 3], type = "u16", SIZE = 10 }
     let cfg = nvmbuilder::layout::load_layout(path.to_str().unwrap()).expect("parse layout");
     let block = cfg.blocks.get("block").expect("block present");
 
-    let res = block.build_bytestream(None, &cfg.settings, false);
+    let res = block.build_bytestream(None, &cfg, false, &BuildInfo::default());
     assert!(res.is_err(), "SIZE should reject underfilled array");
     let err_msg = format!("{:?}", res.unwrap_err());
     assert!(err_msg.contains("smaller than defined size"));
@@ -132,7 +134,7 @@ matrix = { name = "CalibrationMatrix", type = "i16", SIZE = [5, 3] }
     };
     let ds = nvmbuilder::variant::DataSheet::new(&var_args).expect("datasheet loads");
 
-    let res = block.build_bytestream(ds.as_ref(), &cfg.settings, false);
+    let res = block.build_bytestream(ds.as_ref(), &cfg, false, &BuildInfo::default());
     assert!(res.is_err(), "SIZE should reject underfilled 2D array");
     let err_msg = format!("{:?}", res.unwrap_err());
     assert!(err_msg.contains("smaller than defined size"));
@@ -174,7 +176,7 @@ both = { value = [1, 2, 3], type = "u16", size = 5, SIZE = 10 }
     let cfg = nvmbuilder::layout::load_layout(path.to_str().unwrap()).expect("parse layout");
     let block = cfg.blocks.get("block").expect("block present");
 
-    let res = block.build_bytestream(None, &cfg.settings, false);
+    let res = block.build_bytestream(None, &cfg, false, &BuildInfo::default());
     assert!(res.is_err(), "Using both size and SIZE should error");
     let err_msg = format!("{:?}", res.unwrap_err());
     assert!(err_msg.contains("Use either 'size' or 'SIZE', not both"));
@@ -217,7 +219,7 @@ exact_array = { value = [1, 2, 3, 4, 5], type = "u16", SIZE = 5 }
     let block = cfg.blocks.get("block").expect("block present");
 
     let (bytes, _padding) = block
-        .build_bytestream(None, &cfg.settings, false)
+        .build_bytestream(None, &cfg, false, &BuildInfo::default())
         .expect("SIZE should accept exact match");
 
     assert!(bytes.len() >= 10);
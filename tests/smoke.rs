@@ -1,4 +1,5 @@
 use nvmbuilder::commands;
+use nvmbuilder::layout::build_info::BuildInfo;
 
 #[path = "common/mod.rs"]
 mod common;
@@ -36,8 +37,13 @@ fn smoke_build_examples_all_formats_and_options() {
                 name: blk.to_string(),
                 file: layout_path.to_string(),
             };
-            commands::generate::build_block_single(&input, Some(&ds), &args_hex)
-                .expect("build hex");
+            commands::generate::build_block_single(
+                &input,
+                Some(&ds),
+                &BuildInfo::default(),
+                &args_hex,
+            )
+            .expect("build hex");
             common::assert_out_file_exists(blk, nvmbuilder::output::args::OutputFormat::Hex);
 
             // Mot
@@ -46,8 +52,13 @@ fn smoke_build_examples_all_formats_and_options() {
                 blk,
                 nvmbuilder::output::args::OutputFormat::Mot,
             );
-            commands::generate::build_block_single(&input, Some(&ds), &args_mot)
-                .expect("build mot");
+            commands::generate::build_block_single(
+                &input,
+                Some(&ds),
+                &BuildInfo::default(),
+                &args_mot,
+            )
+            .expect("build mot");
             common::assert_out_file_exists(blk, nvmbuilder::output::args::OutputFormat::Mot);
         }
 
@@ -2,6 +2,8 @@ use nvmbuilder::commands::{
     self,
     stats::{BlockStat, BuildStats},
 };
+use nvmbuilder::layout::build_info::BuildInfo;
+use nvmbuilder::layout::settings::CrcWidth;
 
 #[path = "common/mod.rs"]
 mod common;
@@ -26,8 +28,9 @@ fn test_block_stat_collection() {
         file: layout_path.to_string(),
     };
 
-    let block_stat = commands::generate::build_block_single(&input, Some(&ds), &args)
-        .expect("build should succeed");
+    let block_stat =
+        commands::generate::build_block_single(&input, Some(&ds), &BuildInfo::default(), &args)
+            .expect("build should succeed");
 
     assert_eq!(block_stat.name, "block");
     assert!(block_stat.allocated_size > 0);
@@ -94,6 +97,8 @@ fn test_space_efficiency_calculation() {
         allocated_size: 100,
         used_size: 80,
         crc_value: 0x12345678,
+        crc_width: CrcWidth::W32,
+        digest: None,
     });
 
     stats.add_block(BlockStat {
@@ -102,6 +107,8 @@ fn test_space_efficiency_calculation() {
         allocated_size: 200,
         used_size: 120,
         crc_value: 0x9ABCDEF0,
+        crc_width: CrcWidth::W32,
+        digest: None,
     });
 
     assert_eq!(stats.blocks_processed, 2);
@@ -164,6 +171,8 @@ fn test_space_efficiency_edge_cases() {
         allocated_size: 100,
         used_size: 100,
         crc_value: 0x12345678,
+        crc_width: CrcWidth::W32,
+        digest: None,
     });
 
     let efficiency = stats.space_efficiency();
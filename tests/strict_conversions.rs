@@ -1,3 +1,4 @@
+use nvmbuilder::layout::build_info::BuildInfo;
 use std::io::Write;
 
 #[path = "common/mod.rs"]
@@ -49,7 +50,7 @@ ok.int_exact_to_f32   = { value = 16777216, type = "f32" }
     let ds = nvmbuilder::variant::DataSheet::new(&var_args).expect("datasheet loads");
 
     let (bytes, _padding) = block
-        .build_bytestream(ds.as_ref(), &cfg.settings, true)
+        .build_bytestream(ds.as_ref(), &cfg, true, &BuildInfo::default())
         .expect("strict conversions should succeed");
     assert!(!bytes.is_empty());
 }
@@ -98,7 +99,7 @@ bad.frac_to_u8 = { value = 1.5, type = "u8" }
     };
     let ds = nvmbuilder::variant::DataSheet::new(&var_args).expect("datasheet loads");
 
-    let res = block.build_bytestream(ds.as_ref(), &cfg.settings, true);
+    let res = block.build_bytestream(ds.as_ref(), &cfg, true, &BuildInfo::default());
     assert!(
         res.is_err(),
         "strict mode should reject fractional float to int"
@@ -149,7 +150,7 @@ bad.large_int_to_f64 = { value = 9007199254740993, type = "f64" }
     };
     let ds = nvmbuilder::variant::DataSheet::new(&var_args).expect("datasheet loads");
 
-    let res = block.build_bytestream(ds.as_ref(), &cfg.settings, true);
+    let res = block.build_bytestream(ds.as_ref(), &cfg, true, &BuildInfo::default());
     assert!(
         res.is_err(),
         "strict mode should reject lossy int to f64 conversion"
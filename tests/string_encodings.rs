@@ -0,0 +1,103 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout(data: &str) -> String {
+    common::InlineLayout {
+        endianness: "big",
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+fn build(file_stem: &str, data: &str) -> Result<Vec<u8>, String> {
+    common::build_inline_block(file_stem, &layout(data), false)
+}
+
+#[test]
+fn cstring_and_pascal_strings() {
+    let bytes = build(
+        "test_string_cstring_pascal",
+        r#"
+c = { value = "abc", type = "u8", size = 5, encoding = "cstring" }
+p8 = { value = "hi", type = "u8", size = 4, encoding = "pascal8" }
+p16 = { value = "hi", type = "u8", size = 4, encoding = "pascal16" }
+"#,
+    )
+    .expect("strings build");
+
+    assert_eq!(&bytes[..5], b"abc\0\xFF");
+    assert_eq!(&bytes[5..9], &[2, b'h', b'i', 0xFF]);
+    // u16 prefix follows the block endianness
+    assert_eq!(&bytes[9..13], &[0, 2, b'h', b'i']);
+}
+
+#[test]
+fn cstring_terminator_must_fit() {
+    let err = build(
+        "test_string_cstring_full",
+        r#"c = { value = "abcd", type = "u8", size = 4, encoding = "cstring" }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("NUL terminator"), "got: {}", err);
+}
+
+#[test]
+fn utf16_strings_use_u16_units() {
+    let bytes = build(
+        "test_string_utf16",
+        r#"
+le = { value = "Aé", type = "u16", size = 3, encoding = "utf16le" }
+be = { value = "Aé", type = "u16", size = 2, encoding = "utf16be" }
+"#,
+    )
+    .expect("utf16 builds");
+
+    assert_eq!(&bytes[..6], &[0x41, 0x00, 0xE9, 0x00, 0xFF, 0xFF]);
+    assert_eq!(&bytes[6..10], &[0x00, 0x41, 0x00, 0xE9]);
+
+    let err = build(
+        "test_string_utf16_type",
+        r#"s = { value = "A", type = "u8", size = 2, encoding = "utf16le" }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("should have type u16"), "got: {}", err);
+}
+
+#[test]
+fn ascii_encoding_rejects_non_ascii() {
+    let err = build(
+        "test_string_ascii",
+        r#"s = { value = "Café", type = "u8", size = 8, encoding = "ascii" }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("Non-ASCII character 'é' at position 3"),
+        "got: {}",
+        err
+    );
+}
+
+#[test]
+fn encoding_requires_a_string_array() {
+    let err = build(
+        "test_string_encoding_scalar",
+        r#"s = { value = 1, type = "u8", encoding = "ascii" }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("only applies to 1D string arrays"),
+        "got: {}",
+        err
+    );
+
+    let err = build(
+        "test_string_encoding_numeric",
+        r#"s = { value = [1, 2, 3], type = "u8", size = 4, encoding = "ascii" }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("'encoding' only applies to strings, not numeric arrays"),
+        "got: {}",
+        err
+    );
+}
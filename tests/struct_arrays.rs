@@ -0,0 +1,97 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::layout::build_info::BuildInfo;
+
+fn layout(data: &str) -> String {
+    common::InlineLayout {
+        length: 0x1000,
+        padding: 0xEE,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+fn build_with_datasheet(file_stem: &str, data: &str) -> Result<Vec<u8>, String> {
+    let layout = layout(data);
+    let path = common::write_layout_file(file_stem, &layout);
+    let cfg = nvmbuilder::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let block = cfg.blocks.get("block").expect("block present");
+    let ds = common::find_working_datasheet().expect("datasheet loads");
+
+    block
+        .build_bytestream(Some(&ds), &cfg, true, &BuildInfo::default())
+        .map(|(bytes, _padding)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn struct_array_rows_map_columns_by_header() {
+    let bytes = build_with_datasheet(
+        "test_struct_array_rows",
+        r#"
+head = { value = 0x42, type = "u8" }
+structs = { name = "AStructs", size = 10, element = { a = { name = "A", type = "u8" }, b = { name = "B", type = "f32" }, tag = { value = 0x7, type = "u8" } } }
+"#,
+    )
+    .expect("struct array should build");
+
+    // Elements are aligned to 4 and padded to a 12 byte stride
+    let stride = 12;
+    assert_eq!(bytes.len(), 4 + 10 * stride);
+    assert_eq!(&bytes[..4], &[0x42, 0xEE, 0xEE, 0xEE]);
+
+    for i in 0..8 {
+        let elem = &bytes[4 + i * stride..4 + (i + 1) * stride];
+        assert_eq!(elem[0], (i + 1) as u8);
+        assert_eq!(&elem[4..8], &((i + 1) as f32).to_le_bytes());
+        assert_eq!(elem[8], 0x7);
+    }
+
+    // Missing rows are filled with padding
+    assert!(bytes[4 + 8 * stride..].iter().all(|b| *b == 0xEE));
+}
+
+#[test]
+fn struct_array_row_count_is_enforced() {
+    let element =
+        r#"element = { a = { name = "A", type = "u8" }, b = { name = "B", type = "f32" } }"#;
+
+    let err = build_with_datasheet(
+        "test_struct_array_strict",
+        &format!(
+            "structs = {{ name = \"AStructs\", SIZE = 10, {} }}",
+            element
+        ),
+    )
+    .unwrap_err();
+    assert!(err.contains("smaller than defined size"), "got: {}", err);
+
+    let err = build_with_datasheet(
+        "test_struct_array_overflow",
+        &format!("structs = {{ name = \"AStructs\", size = 4, {} }}", element),
+    )
+    .unwrap_err();
+    assert!(err.contains("greater than defined size"), "got: {}", err);
+
+    build_with_datasheet(
+        "test_struct_array_exact",
+        &format!("structs = {{ name = \"AStructs\", SIZE = 8, {} }}", element),
+    )
+    .expect("exact row count should build");
+}
+
+#[test]
+fn struct_array_missing_column_names_row() {
+    let err = build_with_datasheet(
+        "test_struct_array_missing_column",
+        r#"structs = { name = "AStructs", size = 8, element = { c = { name = "C", type = "u8" } } }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("In field '[0]'"), "got: {}", err);
+    assert!(
+        err.contains("Column 'C' is missing or empty"),
+        "got: {}",
+        err
+    );
+}
@@ -0,0 +1,87 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use nvmbuilder::layout::build_info::BuildInfo;
+
+const TYPES: &str = r#"
+[types.Point3f]
+x = { value = 1.0, type = "f32" }
+y = { value = 2.0, type = "f32" }
+z = { value = 3.0, type = "f32" }
+
+[types.Version]
+major = { name = "Major", type = "u16" }
+minor = { name = "Minor", type = "u16" }
+patch = { name = "Patch", type = "u16" }
+
+[types.Pose]
+tag = { value = 0x7A, type = "u8" }
+position = { type = "Point3f" }
+"#;
+
+fn with_types(types: &str, data: &str) -> String {
+    common::InlineLayout {
+        padding: 0xEE,
+        tables: types,
+        ..Default::default()
+    }
+    .with_data(data)
+}
+
+#[test]
+fn type_references_expand_with_alignment() {
+    let layout = with_types(
+        TYPES,
+        r#"
+flag = { value = 0x01, type = "u8" }
+pose = { type = "Pose" }
+"#,
+    );
+
+    let bytes = common::build_inline_block("test_types_expand", &layout, true)
+        .expect("type references should expand");
+
+    let mut expected = vec![0x01, 0x7A, 0xEE, 0xEE];
+    for v in [1.0f32, 2.0, 3.0] {
+        expected.extend(v.to_le_bytes());
+    }
+    assert_eq!(&bytes[..16], expected.as_slice());
+}
+
+#[test]
+fn name_prefix_is_applied_to_excel_names() {
+    let layout = with_types(
+        TYPES,
+        r#"
+typed = { type = "Version", name_prefix = "FWVersion" }
+direct.major = { name = "FWVersionMajor", type = "u16" }
+direct.minor = { name = "FWVersionMinor", type = "u16" }
+direct.patch = { name = "FWVersionPatch", type = "u16" }
+"#,
+    );
+
+    let path = common::write_layout_file("test_types_prefix", &layout);
+    let cfg = nvmbuilder::layout::load_layout(&path).expect("layout loads");
+    let block = cfg.blocks.get("block").expect("block present");
+    let ds = common::find_working_datasheet().expect("datasheet loads");
+
+    let (bytes, _padding) = block
+        .build_bytestream(Some(&ds), &cfg, false, &BuildInfo::default())
+        .expect("prefixed names resolve");
+    assert_eq!(&bytes[..6], &bytes[6..12]);
+}
+
+#[test]
+fn unknown_and_recursive_types_are_rejected() {
+    let layout = with_types(TYPES, r#"cal = { type = "SensorCal" }"#);
+    let err = common::build_inline_block("test_types_unknown", &layout, false).unwrap_err();
+    assert!(err.contains("Type not found: SensorCal"), "got: {}", err);
+
+    let recursive = TYPES.replace(
+        "[types.Version]",
+        "[types.Version]\nnested = { type = \"Version\" }",
+    );
+    let layout = with_types(&recursive, r#"v = { type = "Version" }"#);
+    let err = common::build_inline_block("test_types_recursive", &layout, false).unwrap_err();
+    assert!(err.contains("Version -> Version"), "got: {}", err);
+}